@@ -0,0 +1,100 @@
+//! Just enough ANSI styling for the formatters,
+//! writing the same escapes as [`tracing_subscriber`] does.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Purple,
+    Cyan,
+    DarkGray,
+}
+
+impl Color {
+    fn code(self) -> u8 {
+        match self {
+            Self::Red => 31,
+            Self::Green => 32,
+            Self::Yellow => 33,
+            Self::Blue => 34,
+            Self::Purple => 35,
+            Self::Cyan => 36,
+            Self::DarkGray => 90,
+        }
+    }
+    pub(crate) fn normal(self) -> Style {
+        Style {
+            foreground: Some(self),
+            ..Style::new()
+        }
+    }
+}
+
+/// A style that paints nothing by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Style {
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    foreground: Option<Color>,
+}
+
+impl Style {
+    pub(crate) const fn new() -> Self {
+        Self {
+            bold: false,
+            dimmed: false,
+            italic: false,
+            foreground: None,
+        }
+    }
+    #[cfg_attr(not(feature = "serde_json1"), expect(dead_code))]
+    pub(crate) fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
+    pub(crate) fn dimmed(self) -> Self {
+        Self {
+            dimmed: true,
+            ..self
+        }
+    }
+    pub(crate) fn italic(self) -> Self {
+        Self {
+            italic: true,
+            ..self
+        }
+    }
+    pub(crate) fn paint<T: fmt::Display>(self, it: T) -> Painted<T> {
+        Painted { style: self, it }
+    }
+}
+
+pub(crate) struct Painted<T> {
+    style: Style,
+    it: T,
+}
+
+impl<T: fmt::Display> fmt::Display for Painted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Style {
+            bold,
+            dimmed,
+            italic,
+            foreground,
+        } = self.style;
+        let codes = [(bold, 1), (dimmed, 2), (italic, 3)]
+            .into_iter()
+            .filter_map(|(on, code)| on.then_some(code))
+            .chain(foreground.map(Color::code))
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+        match codes.is_empty() {
+            true => write!(f, "{}", self.it),
+            false => write!(f, "\x1b[{}m{}\x1b[0m", codes.join(";"), self.it),
+        }
+    }
+}
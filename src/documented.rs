@@ -0,0 +1,202 @@
+//! Render [`Subscriber::documented_default`](crate::Subscriber::documented_default)
+//! by walking the [`JsonSchema`](schemars::JsonSchema) of [`Subscriber`].
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use serde::Deserialize as _;
+use serde_json::{Map, Value};
+
+use crate::{Filter, Format, Formatter, Subscriber, Timer, Writer};
+
+/// What [`Subscriber::default`] does, with every option that has a default spelled out.
+///
+/// Options that are only looked at when set, like [`Format::max_message_len`],
+/// or that depend on the environment, like [`Format::ansi`], are left unset.
+pub(crate) fn defaults() -> Subscriber {
+    Subscriber {
+        format: Some(Format {
+            target: Some(true),
+            level: Some(true),
+            thread_ids: Some(false),
+            thread_names: Some(false),
+            file: Some(false),
+            line_number: Some(false),
+            formatter: Some(Formatter::Full),
+            timer: Some(Timer::System),
+            span_events: Some(tracing_subscriber::fmt::format::FmtSpan::NONE),
+            humanize_fields: Some(BTreeMap::new()),
+            os_thread_id: Some(false),
+            shutdown_summary: Some(false),
+            constant_fields: Some(BTreeMap::new()),
+            error_chain: Some(false),
+            env_logger_compat: Some(false),
+            timestamp_precision_by_level: Some(BTreeMap::new()),
+            fields_before_message: Some(false),
+            hide_default_target: Some(false),
+            span_id: Some(false),
+            field_case: Some(crate::FieldCase::AsIs),
+            collapse_repeats: Some(false),
+            relative_time: Some(false),
+            thread_combined: Some(false),
+            parent_span_name: Some(false),
+            uptime_field: Some(false),
+            ..Default::default()
+        }),
+        writer: Some(Writer::Stdout),
+        filter: Some(Filter {
+            regex: Some(true),
+            directives: vec![],
+            default_level: None,
+            mute_targets: Some(vec![]),
+        }),
+        log_bridge: Some(false),
+        total_byte_budget: None,
+    }
+}
+
+pub(crate) fn toml() -> String {
+    let schema = serde_json::to_value(schemars::schema_for!(Subscriber)).unwrap();
+    let defs = schema.get("$defs").and_then(Value::as_object);
+    let defaults = serde_json::to_value(defaults()).unwrap();
+
+    let mut root = String::new();
+    let mut tables = String::new();
+    if let Some(description) = description(&schema, &schema) {
+        comment(&mut root, description);
+        root.push('\n');
+    }
+    for (key, property) in properties(&schema) {
+        let resolved = resolve(property, defs);
+        match properties(resolved).next() {
+            // Tables must come after all the keys in the root table.
+            Some(_) => {
+                tables.push('\n');
+                if let Some(description) = description(property, resolved) {
+                    comment(&mut tables, description);
+                }
+                writeln!(tables, "[{key}]").unwrap();
+                let defaults = defaults.get(key);
+                for (key, property) in properties(resolved) {
+                    tables.push('\n');
+                    let default = defaults.and_then(|it| it.get(key));
+                    option(&mut tables, key, property, resolve(property, defs), default);
+                }
+            }
+            None => option(&mut root, key, property, resolved, defaults.get(key)),
+        }
+    }
+    root + &tables
+}
+
+/// Follow `$ref`s, and strip the `null` alternative from [`Option`]s.
+fn resolve<'a>(mut schema: &'a Value, defs: Option<&'a Map<String, Value>>) -> &'a Value {
+    loop {
+        if let Some(name) = schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|it| it.strip_prefix("#/$defs/"))
+        {
+            match defs.and_then(|it| it.get(name)) {
+                Some(it) => schema = it,
+                None => return schema,
+            }
+            continue;
+        }
+        if let Some([it, null]) = schema
+            .get("anyOf")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+        {
+            if null.get("type").and_then(Value::as_str) == Some("null") {
+                schema = it;
+                continue;
+            }
+        }
+        return schema;
+    }
+}
+
+fn properties(schema: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+}
+
+/// Prefer the documentation on the field, falling back to that on its type.
+fn description<'a>(property: &'a Value, resolved: &'a Value) -> Option<&'a str> {
+    property
+        .get("description")
+        .or_else(|| resolved.get("description"))
+        .and_then(Value::as_str)
+}
+
+fn comment(out: &mut String, description: &str) {
+    for line in description.lines() {
+        match line.trim() {
+            "" => out.push_str("#\n"),
+            line => writeln!(out, "# {line}").unwrap(),
+        }
+    }
+}
+
+/// Set to `default` if there is one, else commented out with an [`example`].
+fn option(
+    out: &mut String,
+    key: &str,
+    property: &Value,
+    resolved: &Value,
+    default: Option<&Value>,
+) {
+    if let Some(description) = description(property, resolved) {
+        comment(out, description);
+    }
+    match default {
+        Some(it) => writeln!(out, "{key} = {}", toml::Value::deserialize(it).unwrap()).unwrap(),
+        None => writeln!(out, "# {key} = {}", example(resolved)).unwrap(),
+    }
+}
+
+/// A representative value of the right shape.
+fn example(schema: &Value) -> String {
+    if let Some(it) = schema
+        .get("const")
+        .or_else(|| schema.get("enum").and_then(|it| it.get(0)))
+    {
+        return toml_value(it);
+    }
+    for key in ["oneOf", "anyOf"] {
+        // Prefer a variant that can be written as a bare string.
+        if let Some(variants) = schema.get(key).and_then(Value::as_array) {
+            let variant = variants
+                .iter()
+                .find(|it| it.get("const").is_some() || it.get("enum").is_some())
+                .or(variants.first());
+            if let Some(it) = variant {
+                return example(it);
+            }
+        }
+    }
+    let ty = match schema.get("type") {
+        Some(Value::Array(types)) => types.iter().find(|it| *it != "null"),
+        other => other,
+    };
+    match ty.and_then(Value::as_str) {
+        Some("boolean") => String::from("false"),
+        Some("integer") => String::from("0"),
+        Some("number") => String::from("0.0"),
+        Some("string") => String::from("\"\""),
+        Some("array") => String::from("[]"),
+        _ => String::from("{}"),
+    }
+}
+
+fn toml_value(value: &Value) -> String {
+    match value {
+        Value::String(it) => toml::Value::String(it.clone()).to_string(),
+        Value::Bool(it) => it.to_string(),
+        Value::Number(it) => it.to_string(),
+        _ => String::from("{}"),
+    }
+}
@@ -1,56 +1,1940 @@
-use tracing_core::{Event, Subscriber};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use tracing_core::{field::Visit, Event, Field, Level, Subscriber};
 use tracing_subscriber::{
+    field::{RecordFields, VisitFmt, VisitOutput},
     fmt::{
         format::{
-            Compact, DefaultFields, Format, Full, Json, JsonFields, Pretty, PrettyFields, Writer,
+            Compact, DefaultFields, DefaultVisitor, FmtSpan, Format, Full, Json, JsonFields,
+            Pretty, PrettyFields, PrettyVisitor, Writer,
         },
-        FmtContext,
+        time::FormatTime as _,
+        FmtContext, FormatEvent as _, FormatFields as _,
     },
     registry::LookupSpan,
 };
 
-use crate::time::FormatTime;
+#[cfg(feature = "serde_json1")]
+use crate::json;
+use crate::{
+    ansi::{Color, Style},
+    time::FormatTime,
+};
 
 /// Implementor of [`tracing_subscriber::fmt::FormatEvent`], constructed [`From`] [`Format`](crate::Format).
-pub struct FormatEvent(FormatEventInner);
+pub struct FormatEvent {
+    inner: FormatEventInner,
+    os_thread_id: bool,
+    span_id: bool,
+    parent_span_name: bool,
+    /// When the subscriber was built, see [`crate::Format::uptime_field`].
+    start: Option<Instant>,
+    event_id: Option<EventId>,
+    /// Only used by the `json` formatter, the others rename fields in [`FormatFields`].
+    field_case: Option<crate::FieldCase>,
+    level_counts: Option<Arc<LevelCounts>>,
+    constant_fields: BTreeMap<String, String>,
+    /// See [`Self::with_dynamic_fields`].
+    dynamic_fields: Option<fn() -> BTreeMap<String, String>>,
+    field_precedence: Option<crate::FieldPrecedence>,
+    error_chain: bool,
+    /// See [`Self::report`].
+    report: Option<Mutex<Option<String>>>,
+    max_message_len: Option<usize>,
+    record_separator: crate::RecordSeparator,
+    error_cooldown: Option<Arc<ErrorCooldown>>,
+    /// See [`crate::Json::colorize`].
+    colorize: bool,
+    /// See [`crate::Json::typed_values`].
+    typed_values: bool,
+    /// See [`crate::Json::message_key`].
+    json_message_key: Option<String>,
+    /// See [`crate::Json::span_records`].
+    span_records: bool,
+    /// Used for bare crate targets, see [`crate::Format::hide_default_target`].
+    without_target: Option<FormatEventInner>,
+    /// Written before the inner formatter, which then omits the time and level.
+    level_icons: Option<LevelIcons>,
+    /// Those requested by the user, see [`Self::span_events`].
+    span_events: FmtSpan,
+    /// Only if [`FmtSpan::CLOSE`] is requested.
+    span_timing: Option<crate::SpanEvents>,
+}
+
+impl FormatEvent {
+    fn new(value: crate::Format, previous: Option<crate::time::Previous>) -> Self {
+        Self {
+            os_thread_id: value.os_thread_id.unwrap_or_default(),
+            span_id: value.span_id.unwrap_or_default(),
+            parent_span_name: value.parent_span_name.unwrap_or_default(),
+            start: match value.uptime_field {
+                Some(true) => Some(Instant::now()),
+                _ => None,
+            },
+            event_id: value.event_id.map(EventId::new),
+            field_case: value.field_case.filter(|it| *it != crate::FieldCase::AsIs),
+            level_counts: match value.shutdown_summary {
+                Some(true) => Some(Arc::default()),
+                _ => None,
+            },
+            constant_fields: value.constant_fields.clone().unwrap_or_default(),
+            dynamic_fields: None,
+            field_precedence: value.field_precedence,
+            error_chain: value.error_chain.unwrap_or_default(),
+            report: None,
+            max_message_len: value.max_message_len,
+            record_separator: match &value.formatter {
+                Some(crate::Formatter::Json(Some(crate::Json {
+                    record_separator: Some(it),
+                    ..
+                }))) => *it,
+                _ => crate::RecordSeparator::Newline,
+            },
+            colorize: matches!(
+                &value.formatter,
+                Some(crate::Formatter::Json(Some(crate::Json {
+                    colorize: Some(true),
+                    ..
+                })))
+            ) && value.ansi != Some(false),
+            typed_values: matches!(
+                &value.formatter,
+                Some(crate::Formatter::Json(Some(crate::Json {
+                    typed_values: Some(true),
+                    ..
+                })))
+            ),
+            span_records: matches!(
+                &value.formatter,
+                Some(crate::Formatter::Json(Some(crate::Json {
+                    span_records: Some(true),
+                    ..
+                })))
+            ),
+            json_message_key: match &value.formatter {
+                Some(crate::Formatter::Json(Some(crate::Json {
+                    message_key: Some(it),
+                    ..
+                }))) if it != "message" => Some(it.clone()),
+                _ => None,
+            },
+            error_cooldown: value.error_cooldown.map(|window| {
+                Arc::new(ErrorCooldown {
+                    window,
+                    seen: Mutex::default(),
+                })
+            }),
+            level_icons: None,
+            span_events: value.span_events.clone().unwrap_or(FmtSpan::NONE),
+            span_timing: value.span_timing.clone().filter(|_| {
+                value
+                    .span_events
+                    .clone()
+                    .is_some_and(|it| it & FmtSpan::CLOSE == FmtSpan::CLOSE)
+            }),
+            without_target: match (&value.hide_default_target, &value.target) {
+                (Some(true), None | Some(true))
+                    if !matches!(value.formatter, Some(crate::Formatter::Json(_))) =>
+                {
+                    Some(FormatEventInner::new(
+                        crate::Format {
+                            target: Some(false),
+                            ..value.clone()
+                        },
+                        previous.clone(),
+                    ))
+                }
+                _ => None,
+            },
+            inner: FormatEventInner::new(value, previous),
+        }
+    }
+}
 
 impl From<crate::Format> for FormatEvent {
     fn from(value: crate::Format) -> Self {
-        Self(value.into())
+        // only text formatters show the delta, see [`crate::Format::relative_time`]
+        let relative_time = value.relative_time.unwrap_or_default()
+            && !matches!(value.formatter, Some(crate::Formatter::Json(_)));
+        let previous = relative_time.then(Arc::default);
+        // the time, level and combined thread are written by `LevelIcons` instead
+        let prefixed = |value: crate::Format| crate::Format {
+            timer: Some(crate::Timer::None),
+            level: Some(false),
+            thread_ids: value.thread_ids.filter(|_| !combines_thread(&value)),
+            thread_names: value.thread_names.filter(|_| !combines_thread(&value)),
+            ..value
+        };
+        match &value {
+            crate::Format {
+                level_icons: Some(icons),
+                formatter:
+                    None
+                    | Some(
+                        crate::Formatter::Full
+                        | crate::Formatter::Compact
+                        | crate::Formatter::Pretty,
+                    ),
+                env_logger_compat: None | Some(false),
+                ..
+            } => Self {
+                level_icons: Some(LevelIcons::new(icons, &value, previous)),
+                ..Self::new(prefixed(value), None)
+            },
+            crate::Format {
+                level_padding: Some(_),
+                formatter: None | Some(crate::Formatter::Full | crate::Formatter::Compact),
+                env_logger_compat: None | Some(false),
+                level: None | Some(true),
+                ..
+            } => Self {
+                level_icons: Some(LevelIcons::new(
+                    &crate::LevelIcons {
+                        icons: Some(BTreeMap::new()),
+                        label: Some(true),
+                    },
+                    &value,
+                    previous,
+                )),
+                ..Self::new(prefixed(value), None)
+            },
+            _ if combines_thread(&value) => Self {
+                level_icons: Some(LevelIcons::new(
+                    &crate::LevelIcons {
+                        icons: Some(BTreeMap::new()),
+                        label: Some(value.level.unwrap_or(true)),
+                    },
+                    &value,
+                    previous,
+                )),
+                ..Self::new(prefixed(value), None)
+            },
+            _ => Self::new(value, previous),
+        }
+    }
+}
+
+/// Whether to write the thread as one token, see [`crate::Format::thread_combined`].
+fn combines_thread(format: &crate::Format) -> bool {
+    matches!(
+        format,
+        crate::Format {
+            thread_combined: Some(true),
+            thread_ids: Some(true),
+            thread_names: Some(true),
+            formatter: None | Some(crate::Formatter::Full | crate::Formatter::Compact),
+            env_logger_compat: None | Some(false),
+            ..
+        }
+    )
+}
+
+/// See [`crate::Format::level_icons`], [`crate::Format::level_padding`]
+/// and [`crate::Format::thread_combined`].
+struct LevelIcons {
+    ansi: Option<bool>,
+    timer: FormatTime,
+    /// Padded to the same width, and empty if only padding the label.
+    icons: BTreeMap<crate::Level, String>,
+    label: bool,
+    padding: usize,
+    thread_combined: bool,
+}
+
+impl LevelIcons {
+    fn new(
+        config: &crate::LevelIcons,
+        format: &crate::Format,
+        previous: Option<crate::time::Previous>,
+    ) -> Self {
+        let crate::LevelIcons { icons, label } = config;
+        let only_label = icons.as_ref().is_some_and(BTreeMap::is_empty);
+        let icons = [
+            (crate::Level::Trace, "⚪"),
+            (crate::Level::Debug, "🟢"),
+            (crate::Level::Info, "🔵"),
+            (crate::Level::Warn, "🟡"),
+            (crate::Level::Error, "🔴"),
+        ]
+        .map(|(level, default)| {
+            let icon = icons.as_ref().and_then(|it| it.get(&level));
+            (level, icon.map(String::as_str).unwrap_or(default))
+        });
+        let max = icons
+            .iter()
+            .map(|(_, it)| width(it))
+            .max()
+            .unwrap_or_default();
+        Self {
+            ansi: format.ansi,
+            timer: FormatTime::from(format.timer.clone().unwrap_or_default())
+                .with_precision_by_level(
+                    format
+                        .timestamp_precision_by_level
+                        .clone()
+                        .unwrap_or_default(),
+                )
+                .with_relative_time(previous),
+            icons: match only_label {
+                true => BTreeMap::new(),
+                false => icons
+                    .into_iter()
+                    .map(|(level, it)| (level, format!("{it}{}", " ".repeat(max - width(it)))))
+                    .collect(),
+            },
+            label: label.unwrap_or_default(),
+            padding: format.level_padding.unwrap_or(5),
+            thread_combined: combines_thread(format),
+        }
+    }
+    /// Write the time and level, like the inner formatter would have.
+    fn write(&self, writer: &mut Writer<'_>, level: &Level) -> fmt::Result {
+        let ansi = self.ansi.unwrap_or(writer.has_ansi_escapes());
+        let style = |it: Style| match ansi {
+            true => it,
+            false => Style::new(),
+        };
+        let mut time = String::new();
+        self.timer.format_time(&mut Writer::new(&mut time))?;
+        if !time.is_empty() {
+            write!(writer, "{}", style(Style::new().dimmed()).paint(time))?
+        }
+        // like the inner formatter, the space is written even without a time,
+        // and it writes another after its own (omitted) time
+        writer.write_str(" ")?;
+        let icon = self.icons.get(&crate::Level::from(*level));
+        if let Some(icon) = icon {
+            writer.write_str(icon)?
+        }
+        if self.label {
+            if icon.is_some() {
+                writer.write_str(" ")?
+            }
+            let color = match *level {
+                Level::TRACE => Color::Purple,
+                Level::DEBUG => Color::Blue,
+                Level::INFO => Color::Green,
+                Level::WARN => Color::Yellow,
+                Level::ERROR => Color::Red,
+            };
+            write!(
+                writer,
+                "{}",
+                style(color.normal()).paint(format!("{:>1$}", level.as_str(), self.padding))
+            )?
+        }
+        if self.thread_combined {
+            if icon.is_some() || self.label {
+                writer.write_str(" ")?
+            }
+            // like the inner formatter, which falls back to the id
+            let thread = std::thread::current();
+            match thread.name() {
+                Some(name) => {
+                    let id = format!("{:?}", thread.id());
+                    let id = id.trim_start_matches("ThreadId(").trim_end_matches(')');
+                    write!(writer, "{name}({id})")?
+                }
+                None => write!(writer, "{:0>2?}", thread.id())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Roughly how many terminal columns `s` takes, counting emoji as two,
+/// and ANSI escapes (like those in a colored icon) as none.
+fn width(s: &str) -> usize {
+    strip_ansi(s)
+        .chars()
+        .map(|it| match it as u32 {
+            0xFE00..=0xFE0F | 0x200D => 0,
+            0x2600..=0x27BF | 0x1F000.. => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Remove ANSI escape sequences like `\x1b[31m` from `s`.
+fn strip_ansi(s: &str) -> Cow<'_, str> {
+    if !s.contains('\x1b') {
+        return Cow::Borrowed(s);
+    }
+    let mut stripped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            // a CSI sequence ends with a byte in `@..=~`
+            '\x1b' => {
+                if chars.next() == Some('[') {
+                    for it in chars.by_ref() {
+                        if ('@'..='~').contains(&it) {
+                            break;
+                        }
+                    }
+                }
+            }
+            c => stripped.push(c),
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// The number of events formatted at each level, see [`crate::Format::shutdown_summary`].
+#[derive(Default)]
+pub(crate) struct LevelCounts([AtomicU64; 5]);
+
+impl LevelCounts {
+    const LEVELS: [Level; 5] = [
+        Level::TRACE,
+        Level::DEBUG,
+        Level::INFO,
+        Level::WARN,
+        Level::ERROR,
+    ];
+    fn increment(&self, level: &Level) {
+        if let Some(ix) = Self::LEVELS.iter().position(|it| it == level) {
+            self.0[ix].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    /// A final line for the log, in the same style as the rest of it.
+    pub(crate) fn summary(&self, json: bool) -> String {
+        let counts = Self::LEVELS
+            .iter()
+            .zip(&self.0)
+            .map(|(level, count)| (level.as_str(), count.load(Ordering::Relaxed)));
+        match json {
+            true => format!(
+                r#"{{"message":"shutdown summary","counts":{{{}}}}}"#,
+                counts
+                    .map(|(k, v)| format!(r#""{k}":{v}"#))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            false => counts.fold(String::from("shutdown summary:"), |acc, (k, v)| {
+                format!("{acc} {k}={v}")
+            }),
+        }
+    }
+}
+
+impl FormatEvent {
+    /// Call `f` for every event to get fields to add after [`crate::Format::constant_fields`],
+    /// e.g a request id from a thread-local.
+    ///
+    /// These are not subject to [`crate::Format::field_precedence`].
+    /// Not supported by the `pretty` formatter,
+    /// and the `json` formatter requires the `json` feature.
+    ///
+    /// Use with e.g [`SubscriberBuilder::map_event_format`](tracing_subscriber::fmt::SubscriberBuilder::map_event_format):
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// let (builder, _guard) = tracing_configuration::Subscriber::default().builder();
+    /// let builder = builder.map_event_format(|it| it.with_dynamic_fields(BTreeMap::new));
+    /// ```
+    pub fn with_dynamic_fields(mut self, f: fn() -> BTreeMap<String, String>) -> Self {
+        self.dynamic_fields = Some(f);
+        self
+    }
+    /// The span events to configure the layer with,
+    /// including those needed to time spans for [`crate::SpanEvents`].
+    pub(crate) fn span_events(&self) -> FmtSpan {
+        match self.span_timing {
+            Some(_) => self.span_events.clone() | FmtSpan::NEW | FmtSpan::ACTIVE,
+            None => self.span_events.clone(),
+        }
+    }
+    /// Update the timings of the span that `event` is from, see [`Self::span_events`].
+    fn time_span<S, N>(&self, ctx: &FmtContext<'_, S, N>, event: &Event<'_>) -> SpanEvent
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+    {
+        let (Some(timing), true, Some(span)) = (
+            &self.span_timing,
+            event.metadata().is_span(),
+            event.parent().and_then(|it| ctx.span(it)),
+        ) else {
+            return SpanEvent::Other;
+        };
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        let now = Instant::now();
+        let mut extensions = span.extensions_mut();
+        let kind = match message.0.as_str() {
+            "new" => {
+                if extensions.get_mut::<SpanTimings>().is_none() {
+                    extensions.insert(SpanTimings {
+                        busy: Duration::ZERO,
+                        idle: Duration::ZERO,
+                        last: now,
+                        entered: 0,
+                    })
+                }
+                FmtSpan::NEW
+            }
+            "enter" => {
+                if let Some(it) = extensions.get_mut::<SpanTimings>() {
+                    it.enter(now)
+                }
+                FmtSpan::ENTER
+            }
+            "exit" => {
+                if let Some(it) = extensions.get_mut::<SpanTimings>() {
+                    it.exit(now)
+                }
+                FmtSpan::EXIT
+            }
+            "close" => {
+                let timings = extensions
+                    .get_mut::<SpanTimings>()
+                    .map(|it| it.close(now))
+                    .filter(|(busy, idle)| {
+                        timing.min_duration.is_none_or(|min| *busy + *idle >= min)
+                    });
+                return SpanEvent::Close(timings);
+            }
+            _ => return SpanEvent::Other,
+        };
+        match self.span_events.clone() & kind.clone() == kind {
+            true => SpanEvent::Other,
+            false => SpanEvent::Unrequested,
+        }
+    }
+    /// Log `error` (from constructing the writer) before the first event,
+    /// see [`crate::Subscriber::try_builder_or_report`].
+    pub(crate) fn report(&mut self, error: String) {
+        self.report = Some(Mutex::new(Some(error)))
+    }
+    /// Per-level counts of formatted events, if they're being kept.
+    pub(crate) fn level_counts(&self) -> Option<(Arc<LevelCounts>, bool)> {
+        let json = matches!(self.inner, FormatEventInner::Json(_));
+        self.level_counts.clone().map(|it| (it, json))
+    }
+    /// Duplicate errors still being suppressed, if they're being suppressed.
+    pub(crate) fn error_cooldown(&self) -> Option<(Arc<ErrorCooldown>, bool)> {
+        let json = matches!(self.inner, FormatEventInner::Json(_));
+        self.error_cooldown.clone().map(|it| (it, json))
+    }
+}
+
+thread_local! {
+    /// Fields that [`FormatEvent`] adds to the event it is currently formatting,
+    /// for [`FormatFields`] to render.
+    static EXTRA_FIELDS: RefCell<Vec<Extra>> = const { RefCell::new(Vec::new()) };
+    /// Fields of the event currently being formatted that lost to another source,
+    /// see [`crate::FieldPrecedence`].
+    static SHADOWED_FIELDS: RefCell<BTreeSet<&'static str>> = const { RefCell::new(BTreeSet::new()) };
+    /// Set while [`FormatEvent`] formats a span's close event, see [`SpanEvent::Close`].
+    static SPAN_TIMINGS: Cell<Option<Option<(Duration, Duration)>>> = const { Cell::new(None) };
+}
+
+/// A field that isn't recorded on the [`Event`] itself,
+/// e.g [`Format::os_thread_id`](crate::Format::os_thread_id).
+type Extra = (Cow<'static, str>, Scalar);
+
+/// The value of an [`Extra`].
+pub(crate) enum Scalar {
+    String(String),
+    U64(u64),
+    F64(f64),
+}
+
+impl From<String> for Scalar {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for Scalar {
+    fn from(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<u64> for Scalar {
+    fn from(value: u64) -> Self {
+        Self::U64(value)
+    }
+}
+
+impl From<f64> for Scalar {
+    fn from(value: f64) -> Self {
+        Self::F64(value)
+    }
+}
+
+#[cfg(feature = "serde_json1")]
+impl From<Scalar> for json::Value {
+    fn from(value: Scalar) -> Self {
+        match value {
+            Scalar::String(it) => Self::String(it),
+            Scalar::U64(it) => Self::Number(it.into()),
+            Scalar::F64(it) => serde_json::Number::from_f64(it).map_or(Self::Null, Self::Number),
+        }
+    }
+}
+
+/// The outcome of [`FormatEvent::resolve`].
+#[derive(Default)]
+struct Resolved {
+    constant: Vec<Extra>,
+    event_shadowed: BTreeSet<&'static str>,
+    span_shadowed: BTreeSet<&'static str>,
+}
+
+impl FormatEvent {
+    /// Format with [`Self::inner`], or a variant of it.
+    fn format_inner<S, N>(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+    {
+        if let Some(it) = &self.level_icons {
+            it.write(&mut writer, event.metadata().level())?
+        }
+        match &self.without_target {
+            Some(it) if !event.metadata().target().contains("::") => {
+                it.format_event(ctx, writer, event)
+            }
+            _ => self.inner.format_event(ctx, writer, event),
+        }
+    }
+    fn extra_fields<S, N>(&self, ctx: &FmtContext<'_, S, N>, constant: Vec<Extra>) -> Vec<Extra>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+    {
+        let mut extras = constant;
+        if let Some(f) = self.dynamic_fields {
+            extras.extend(f().into_iter().map(|(k, v)| (Cow::Owned(k), v.into())))
+        }
+        if self.os_thread_id {
+            if let Some(it) = os_thread_id() {
+                extras.push((Cow::Borrowed("os_tid"), it.into()))
+            }
+        }
+        if let Some(it) = self.event_id.as_ref().and_then(EventId::next) {
+            extras.push((Cow::Borrowed("event_id"), it))
+        }
+        if self.span_id {
+            if let Some(it) = ctx.parent_span() {
+                extras.push((Cow::Borrowed("span_id"), it.id().into_u64().into()))
+            }
+        }
+        if self.parent_span_name {
+            if let Some(it) = ctx.parent_span() {
+                extras.push((Cow::Borrowed("parent_span"), it.name().into()))
+            }
+        }
+        if let Some(start) = self.start {
+            extras.push((
+                Cow::Borrowed("uptime_secs"),
+                start.elapsed().as_secs_f64().into(),
+            ))
+        }
+        extras
+    }
+    /// Decide which fields to render when sources collide,
+    /// see [`crate::Format::field_precedence`].
+    fn resolve<S, N>(&self, ctx: &FmtContext<'_, S, N>, event: &Event<'_>) -> Resolved
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+    {
+        use crate::FieldPrecedence as P;
+        if self.constant_fields.is_empty() && self.field_precedence.is_none() {
+            return Resolved::default();
+        }
+        let event_keys = event.fields().map(|it| it.name()).collect::<BTreeSet<_>>();
+        let span_keys = ctx
+            .event_scope()
+            .into_iter()
+            .flatten()
+            .flat_map(|span| span.metadata().fields().iter().map(|it| it.name()))
+            .collect::<BTreeSet<_>>();
+        let is_constant = |k: &&str| self.constant_fields.contains_key(*k);
+        let constant = self
+            .constant_fields
+            .iter()
+            .filter(|(k, _)| {
+                self.field_precedence == Some(P::Constant)
+                    || !(event_keys.contains(k.as_str()) || span_keys.contains(k.as_str()))
+            })
+            .map(|(k, v)| (Cow::Owned(k.clone()), v.clone().into()))
+            .collect();
+        let (event_shadowed, span_shadowed) = match self.field_precedence {
+            None => (BTreeSet::new(), BTreeSet::new()),
+            Some(P::Event) => (BTreeSet::new(), &span_keys & &event_keys),
+            Some(P::Span) => (&event_keys & &span_keys, BTreeSet::new()),
+            Some(P::Constant) => (
+                event_keys.iter().copied().filter(is_constant).collect(),
+                span_keys
+                    .iter()
+                    .copied()
+                    .filter(|k| is_constant(k) || event_keys.contains(k))
+                    .collect(),
+            ),
+        };
+        Resolved {
+            constant,
+            event_shadowed,
+            span_shadowed,
+        }
+    }
+}
+
+/// See [`crate::Format::event_id`].
+pub(crate) struct EventId {
+    kind: crate::EventIdKind,
+    counter: AtomicU64,
+}
+
+impl EventId {
+    pub(crate) fn new(kind: crate::EventIdKind) -> Self {
+        Self {
+            kind,
+            counter: AtomicU64::new(0),
+        }
+    }
+    /// [`None`] if the required feature is disabled.
+    pub(crate) fn next(&self) -> Option<Scalar> {
+        match self.kind {
+            crate::EventIdKind::Counter => {
+                Some(self.counter.fetch_add(1, Ordering::Relaxed).into())
+            }
+            crate::EventIdKind::Uuid => {
+                #[cfg(feature = "uuid1")]
+                {
+                    Some(uuid::Uuid::new_v4().to_string().into())
+                }
+                #[cfg(not(feature = "uuid1"))]
+                {
+                    None
+                }
+            }
+            crate::EventIdKind::Ulid => {
+                #[cfg(feature = "ulid1")]
+                {
+                    Some(ulid::Ulid::new().to_string().into())
+                }
+                #[cfg(not(feature = "ulid1"))]
+                {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// The OS-level id of the current thread, if supported.
+pub(crate) fn os_thread_id() -> Option<u64> {
+    #[cfg(all(feature = "libc0_2", any(target_os = "linux", target_os = "android")))]
+    {
+        // SAFETY: always succeeds
+        Some(unsafe { libc::gettid() } as u64)
+    }
+    #[cfg(all(feature = "libc0_2", target_vendor = "apple"))]
+    {
+        let mut tid = 0;
+        // SAFETY: `0` is the current thread, and `tid` is valid for writes
+        (unsafe { libc::pthread_threadid_np(0, &mut tid) } == 0).then_some(tid)
+    }
+    #[cfg(not(all(
+        feature = "libc0_2",
+        any(target_os = "linux", target_os = "android", target_vendor = "apple")
+    )))]
+    {
+        None
+    }
+}
+
+impl<S, N> tracing_subscriber::fmt::FormatEvent<S, N> for FormatEvent
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let format = || {
+            crate::time::with_level(*event.metadata().level(), || {
+                self.format_event_inner(ctx, writer, event)
+            })
+        };
+        match self.time_span(ctx, event) {
+            SpanEvent::Unrequested => Ok(()),
+            SpanEvent::Close(timings) => {
+                SPAN_TIMINGS.set(Some(timings));
+                let res = format();
+                SPAN_TIMINGS.set(None);
+                res
+            }
+            SpanEvent::Other => format(),
+        }
+    }
+}
+
+/// See [`FormatEvent::time_span`].
+enum SpanEvent {
+    /// Only seen so that the span can be timed.
+    Unrequested,
+    /// The span's busy and idle time, if it was open for long enough.
+    Close(Option<(Duration, Duration)>),
+    Other,
+}
+
+/// The busy and idle time of a span, kept in its extensions,
+/// see [`crate::SpanEvents`].
+///
+/// Measured like [`tracing_subscriber`] does, which doesn't expose its own.
+struct SpanTimings {
+    busy: Duration,
+    idle: Duration,
+    last: Instant,
+    /// Other layers may see the same enters and exits, which is harmless.
+    entered: usize,
+}
+
+impl SpanTimings {
+    fn enter(&mut self, now: Instant) {
+        if self.entered == 0 {
+            self.idle += now - self.last;
+            self.last = now;
+        }
+        self.entered += 1;
+    }
+    fn exit(&mut self, now: Instant) {
+        self.entered = self.entered.saturating_sub(1);
+        if self.entered == 0 {
+            self.busy += now - self.last;
+            self.last = now;
+        }
+    }
+    fn close(&self, now: Instant) -> (Duration, Duration) {
+        (self.busy, self.idle + (now - self.last))
+    }
+}
+
+impl FormatEvent {
+    fn format_event_inner<S, N>(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+    {
+        if let Some(error) = self
+            .report
+            .as_ref()
+            .and_then(|it| it.lock().unwrap_or_else(PoisonError::into_inner).take())
+        {
+            let fields = REPORT.fields();
+            let field = |name| fields.field(name).expect("declared in `REPORT`");
+            let message = format_args!("couldn't create the configured writer, using stderr");
+            self.format_event(
+                ctx,
+                writer.by_ref(),
+                &Event::new(
+                    &REPORT,
+                    &fields.value_set(&[
+                        (
+                            &field("message"),
+                            Some(&message as &dyn tracing_core::field::Value),
+                        ),
+                        (&field("error"), Some(&error.as_str() as _)),
+                    ]),
+                ),
+            )?
+        }
+        if let Some(it) = &self.level_counts {
+            it.increment(event.metadata().level())
+        }
+        if let Some(cooldown) = &self.error_cooldown {
+            let (suppress, ended) = cooldown.check(event);
+            for Burst {
+                target,
+                message,
+                suppressed,
+                ..
+            } in ended
+            {
+                let fields = DUPLICATES.fields();
+                let field = |name| fields.field(name).expect("declared in `DUPLICATES`");
+                let summary = format_args!("suppressed duplicate errors");
+                self.format_event(
+                    ctx,
+                    writer.by_ref(),
+                    &Event::new(
+                        &DUPLICATES,
+                        &fields.value_set(&[
+                            (
+                                &field("message"),
+                                Some(&summary as &dyn tracing_core::field::Value),
+                            ),
+                            (&field("error"), Some(&message.as_str() as _)),
+                            (&field("error.target"), Some(&target.as_str() as _)),
+                            (&field("repeated"), Some(&suppressed as _)),
+                        ]),
+                    ),
+                )?
+            }
+            if suppress {
+                return Ok(());
+            }
+        }
+        if let crate::RecordSeparator::Rs = self.record_separator {
+            writer.write_char('\x1e')?
+        }
+        let Resolved {
+            constant,
+            event_shadowed,
+            span_shadowed,
+        } = self.resolve(ctx, event);
+        let extras = self.extra_fields(ctx, constant);
+        // the json formatter's output can only be edited with `serde_json`
+        let json = cfg!(feature = "serde_json1") && matches!(self.inner, FormatEventInner::Json(_));
+        let mut errors = ErrorChains(vec![]);
+        if self.error_chain && json {
+            event.record(&mut errors)
+        }
+        let rewrite = !event_shadowed.is_empty()
+            || !span_shadowed.is_empty()
+            || !errors.0.is_empty()
+            || (json && self.max_message_len.is_some())
+            || self.colorize
+            || self.field_case.is_some()
+            || self.typed_values
+            || self.json_message_key.is_some()
+            || (self.span_records && event.metadata().is_span());
+        if extras.is_empty() && !rewrite {
+            return self.format_inner(ctx, writer, event);
+        }
+        match &self.inner {
+            #[cfg(feature = "serde_json1")]
+            FormatEventInner::Json(it) if !rewrite => {
+                // append to the object rather than re-parsing it, which would reorder its keys
+                let mut buf = String::new();
+                it.format_event(ctx, Writer::new(&mut buf), event)?;
+                let object = buf.trim_end().strip_suffix('}').ok_or(fmt::Error)?;
+                writer.write_str(object)?;
+                for (ix, (k, v)) in extras.into_iter().enumerate() {
+                    if ix != 0 || !object.ends_with('{') {
+                        writer.write_char(',')?
+                    }
+                    json::write_str(&mut writer, &k)?;
+                    write!(writer, ":{}", json::Value::from(v))?
+                }
+                writeln!(writer, "}}")
+            }
+            #[cfg(feature = "serde_json1")]
+            FormatEventInner::Json(it) => {
+                let mut buf = String::new();
+                it.format_event(ctx, Writer::new(&mut buf), event)?;
+                let mut object = json::Map::parse(&buf).ok_or(fmt::Error)?;
+                if self.span_records && event.metadata().is_span() {
+                    object = span_record(object, event.metadata().name())
+                }
+                let remove = |object: &mut json::Map, keys: &BTreeSet<_>| {
+                    object.retain(|k, _| !keys.contains(k))
+                };
+                let fields = event_fields(&mut object);
+                remove(fields, &event_shadowed);
+                for (name, chain) in errors.0 {
+                    if fields.contains_key(name) {
+                        fields.insert(name.into(), chain.into());
+                    }
+                }
+                if let (Some(max), Some(json::Value::String(message))) =
+                    (self.max_message_len, fields.get_mut("message"))
+                {
+                    if let Cow::Owned(it) = truncate(message, max) {
+                        *message = it
+                    }
+                }
+                if self.typed_values {
+                    type_values(
+                        event_fields(&mut object),
+                        &[JSON_KEYS, &["message"]].concat(),
+                    )
+                }
+                if let Some(case) = self.field_case {
+                    // the metadata keys are only mixed in when flattened
+                    rename_keys(event_fields(&mut object), case, JSON_KEYS)
+                }
+                if let Some(key) = &self.json_message_key {
+                    let fields = event_fields(&mut object);
+                    *fields = std::mem::take(fields)
+                        .into_iter()
+                        .map(|(k, v)| match k == "message" {
+                            true => (key.clone(), v),
+                            false => (k, v),
+                        })
+                        .collect()
+                }
+                if let Some(json::Value::Object(it)) = object.get_mut("span") {
+                    remove(it, &span_shadowed);
+                    if self.typed_values {
+                        type_values(it, &["name"])
+                    }
+                    if let Some(case) = self.field_case {
+                        rename_keys(it, case, &["name"])
+                    }
+                }
+                if let Some(json::Value::Array(it)) = object.get_mut("spans") {
+                    for it in it {
+                        if let json::Value::Object(it) = it {
+                            remove(it, &span_shadowed);
+                            if self.typed_values {
+                                type_values(it, &["name"])
+                            }
+                            if let Some(case) = self.field_case {
+                                rename_keys(it, case, &["name"])
+                            }
+                        }
+                    }
+                }
+                object.extend(extras.into_iter().map(|(k, v)| (k.into_owned(), v.into())));
+                let object = json::Value::Object(object);
+                match self.colorize {
+                    true => writeln!(writer, "{}", Colorized(&object)),
+                    false => writeln!(writer, "{object}"),
+                }
+            }
+            _ => {
+                EXTRA_FIELDS.set(extras);
+                SHADOWED_FIELDS.set(event_shadowed);
+                let res = self.format_inner(ctx, writer, event);
+                EXTRA_FIELDS.take();
+                SHADOWED_FIELDS.take();
+                res
+            }
+        }
+    }
+}
+
+/// The fields of a JSON event, which are only nested if it isn't
+/// [flattened](crate::Json::flatten_event).
+#[cfg(feature = "serde_json1")]
+fn event_fields(object: &mut json::Map) -> &mut json::Map {
+    match matches!(object.get("fields"), Some(json::Value::Object(_))) {
+        true => match object.get_mut("fields") {
+            Some(json::Value::Object(it)) => it,
+            _ => unreachable!("checked above"),
+        },
+        false => object,
+    }
+}
+
+/// Reshape an event synthesized for a span's lifecycle, see [`crate::Json::span_records`].
+#[cfg(feature = "serde_json1")]
+fn span_record(mut object: json::Map, name: &str) -> json::Map {
+    let event = match event_fields(&mut object)
+        .get("message")
+        .and_then(json::Value::as_str)
+    {
+        Some("new") => "span_open",
+        Some("enter") => "span_enter",
+        Some("exit") => "span_exit",
+        Some("close") => "span_close",
+        _ => return object,
+    };
+    // flatten, so e.g `time.busy` is at the top level
+    let fields = std::mem::take(event_fields(&mut object));
+    object.remove("fields");
+    object.extend(fields);
+    object.remove("message");
+    let span = object.remove("span");
+    if let Some(json::Value::Array(it)) = object.get_mut("spans") {
+        // the span is in the list while it's entered
+        if it.last().is_some_and(|it| Some(it) == span.as_ref()) {
+            it.pop();
+        }
+    }
+    let span = match span {
+        Some(json::Value::Object(mut it)) => {
+            it.remove("name");
+            it
+        }
+        _ => json::Map::new(),
+    };
+    let mut record = json::Map::new();
+    for key in ["timestamp", "level"] {
+        if let Some(it) = object.remove(key) {
+            record.insert(key.into(), it);
+        }
+    }
+    record.insert("event".into(), event.into());
+    record.insert("name".into(), name.into());
+    if let Some(it) = object.remove("target") {
+        record.insert("target".into(), it);
+    }
+    record.insert("fields".into(), span.into());
+    record.extend(object);
+    record
+}
+
+/// Keys written by [`Json`] itself.
+#[cfg(feature = "serde_json1")]
+pub(crate) const JSON_KEYS: &[&str] = &[
+    "timestamp",
+    "level",
+    "target",
+    "filename",
+    "line_number",
+    "threadName",
+    "threadId",
+    "span",
+    "spans",
+];
+
+/// See [`crate::Json::typed_values`].
+#[cfg(feature = "serde_json1")]
+fn type_values(object: &mut json::Map, except: &[&str]) {
+    for (k, v) in object.iter_mut() {
+        if except.contains(&k.as_str()) {
+            continue;
+        }
+        let json::Value::String(s) = v else { continue };
+        // only values that are written back exactly, so e.g `1.10` isn't rewritten
+        let typed = match s.as_str() {
+            "true" => Some(json::Value::Bool(true)),
+            "false" => Some(json::Value::Bool(false)),
+            s => match (s.parse::<u64>(), s.parse::<i64>()) {
+                (Ok(it), _) if it.to_string() == s => Some(json::Value::Number(it.into())),
+                (_, Ok(it)) if it.to_string() == s => Some(json::Value::Number(it.into())),
+                _ => None,
+            },
+        };
+        if let Some(it) = typed {
+            *v = it
+        }
+    }
+}
+
+/// See [`crate::Format::field_case`].
+#[cfg(feature = "serde_json1")]
+fn rename_keys(object: &mut json::Map, case: crate::FieldCase, except: &[&str]) {
+    *object = std::mem::take(object)
+        .into_iter()
+        .map(|(k, v)| match except.contains(&k.as_str()) {
+            true => (k, v),
+            false => (case.apply(&k), v),
+        })
+        .collect()
+}
+
+impl crate::FieldCase {
+    fn apply(self, name: &str) -> String {
+        let parts = name.split('.').map(|part| {
+            let words = words(part);
+            match self {
+                Self::AsIs => part.into(),
+                Self::Snake => words.join("_"),
+                Self::Kebab => words.join("-"),
+                Self::Camel => words
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, word)| match ix {
+                        0 => word.clone(),
+                        _ => {
+                            let mut chars = word.chars();
+                            chars
+                                .next()
+                                .map(|it| it.to_uppercase().chain(chars).collect())
+                                .unwrap_or_default()
+                        }
+                    })
+                    .collect(),
+            }
+        });
+        parts.collect::<Vec<_>>().join(".")
+    }
+}
+
+/// Split `s` into lowercase words at `_`, `-` and case changes,
+/// keeping acronyms like the `HTTP` in `HTTPServer` together.
+fn words(s: &str) -> Vec<String> {
+    let chars = s.chars().collect::<Vec<_>>();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (ix, c) in chars.iter().copied().enumerate() {
+        if matches!(c, '_' | '-') {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            continue;
+        }
+        let prev = ix.checked_sub(1).map(|it| chars[it]);
+        let next = chars.get(ix + 1);
+        if c.is_uppercase()
+            && !word.is_empty()
+            && prev.is_some_and(|prev| {
+                prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next.is_some_and(|it| it.is_lowercase()))
+            })
+        {
+            words.push(std::mem::take(&mut word))
+        }
+        word.extend(c.to_lowercase())
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
+
+/// Passes fields to `inner` under their [renamed](crate::Format::field_case) names.
+struct Renaming<V> {
+    inner: V,
+    case: Option<crate::FieldCase>,
+}
+
+impl<V> Renaming<V> {
+    fn rename(&self, field: &Field) -> Field {
+        /// [`Field`] names must be `'static`, so leak each rename once.
+        static RENAMED: std::sync::LazyLock<Mutex<HashMap<(&str, crate::FieldCase), Field>>> =
+            std::sync::LazyLock::new(Mutex::default);
+        let Some(case) = self.case else {
+            return field.clone();
+        };
+        let name = case.apply(field.name());
+        if name == field.name() {
+            return field.clone();
+        }
+        RENAMED
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry((field.name(), case))
+            .or_insert_with(|| {
+                let names = &*Box::leak(Box::new([&*name.leak()]));
+                // the callsite only identifies the field, which we never compare
+                tracing_core::field::FieldSet::new(
+                    names,
+                    tracing_core::identify_callsite!(&REPORT_CALLSITE),
+                )
+                .field(names[0])
+                .expect("just created")
+            })
+            .clone()
+    }
+}
+
+impl<V: Visit> Visit for Renaming<V> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.inner.record_f64(&self.rename(field), value)
+    }
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.inner.record_i64(&self.rename(field), value)
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.inner.record_u64(&self.rename(field), value)
+    }
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.inner.record_i128(&self.rename(field), value)
+    }
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.inner.record_u128(&self.rename(field), value)
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.inner.record_bool(&self.rename(field), value)
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.inner.record_str(&self.rename(field), value)
+    }
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.inner.record_error(&self.rename(field), value)
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.inner.record_debug(&self.rename(field), value)
+    }
+}
+
+impl<V: VisitFmt> VisitFmt for Renaming<V> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        self.inner.writer()
+    }
+}
+
+impl<V: VisitOutput<fmt::Result>> VisitOutput<fmt::Result> for Renaming<V> {
+    fn finish(self) -> fmt::Result {
+        self.inner.finish()
+    }
+}
+
+/// Quotes the values that [`Logfmt`] needs to, which `inner` would write as-is.
+struct Quoting<V>(V);
+
+/// Whether a [`Logfmt`] value must be quoted to be parsed back.
+fn needs_quotes(value: &str) -> bool {
+    value.is_empty() || value.contains(|it: char| it.is_whitespace() || it == '=' || it == '"')
+}
+
+impl<V: Visit> Visit for Quoting<V> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.record_f64(field, value)
+    }
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.record_i64(field, value)
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.record_u64(field, value)
+    }
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.0.record_i128(field, value)
+    }
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.0.record_u128(field, value)
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.record_bool(field, value)
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.record_str(field, value)
+    }
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        // rather than `inner`'s unquoted `.sources`
+        self.record_debug(field, &format_args!("{value}"))
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // already written as `key="..."`, see [`crate::Logfmt::message_key`]
+        if field.name() == "message" {
+            return self.0.record_debug(field, value);
+        }
+        let value = format!("{value:?}");
+        match needs_quotes(&value) {
+            true => self.0.record_debug(field, &value),
+            false => self.0.record_debug(field, &format_args!("{value}")),
+        }
+    }
+}
+
+impl<V: VisitFmt> VisitFmt for Quoting<V> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        self.0.writer()
+    }
+}
+
+impl<V: VisitOutput<fmt::Result>> VisitOutput<fmt::Result> for Quoting<V> {
+    fn finish(self) -> fmt::Result {
+        self.0.finish()
+    }
+}
+
+/// Collects the [`ErrorChain`] of every error field, see [`crate::Format::error_chain`].
+struct ErrorChains(Vec<(&'static str, Vec<String>)>);
+
+impl Visit for ErrorChains {
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        let chain = std::iter::successors(Some(value), |it| it.source());
+        self.0
+            .push((field.name(), chain.map(ToString::to_string).collect()))
+    }
+    fn record_debug(&mut self, _: &Field, _: &dyn fmt::Debug) {}
+}
+
+/// Truncate `s` to `max` characters, marking it with `…`,
+/// see [`crate::Format::max_message_len`].
+fn truncate(s: &str, max: usize) -> Cow<'_, str> {
+    match s.char_indices().nth(max) {
+        Some((ix, _)) => Cow::Owned(format!("{}…", &s[..ix])),
+        None => Cow::Borrowed(s),
+    }
+}
+
+/// Renders an error and its sources like `outer; caused by: inner`.
+struct ErrorChain<'a>(&'a (dyn std::error::Error + 'static));
+
+impl fmt::Display for ErrorChain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        let mut source = self.0.source();
+        while let Some(it) = source {
+            write!(f, "; caused by: {it}")?;
+            source = it.source();
+        }
+        Ok(())
+    }
+}
+
+static REPORT_CALLSITE: tracing_core::callsite::DefaultCallsite =
+    tracing_core::callsite::DefaultCallsite::new(&REPORT);
+/// The event for [`FormatEvent::report`].
+static REPORT: tracing_core::Metadata<'static> = tracing_core::metadata! {
+    name: "writer error",
+    target: env!("CARGO_CRATE_NAME"),
+    level: Level::ERROR,
+    fields: &["message", "error"],
+    callsite: &REPORT_CALLSITE,
+    kind: tracing_core::metadata::Kind::EVENT,
+};
+
+static DUPLICATES_CALLSITE: tracing_core::callsite::DefaultCallsite =
+    tracing_core::callsite::DefaultCallsite::new(&DUPLICATES);
+/// The event summarising a [`Burst`].
+static DUPLICATES: tracing_core::Metadata<'static> = tracing_core::metadata! {
+    name: "duplicate errors",
+    target: env!("CARGO_CRATE_NAME"),
+    level: Level::ERROR,
+    fields: &["message", "error", "error.target", "repeated"],
+    callsite: &DUPLICATES_CALLSITE,
+    kind: tracing_core::metadata::Kind::EVENT,
+};
+
+/// Suppresses bursts of identical errors, see [`crate::Format::error_cooldown`].
+pub(crate) struct ErrorCooldown {
+    window: Duration,
+    /// Keyed by a hash of the target and message.
+    seen: Mutex<HashMap<u64, Burst>>,
+}
+
+struct Burst {
+    start: Instant,
+    target: String,
+    message: String,
+    suppressed: u64,
+}
+
+impl ErrorCooldown {
+    /// Returns whether `event` should be suppressed,
+    /// and any bursts whose window has closed with suppressed events.
+    fn check(&self, event: &Event<'_>) -> (bool, Vec<Burst>) {
+        use std::hash::{BuildHasher as _, RandomState};
+        static HASHER: std::sync::LazyLock<RandomState> =
+            std::sync::LazyLock::new(RandomState::new);
+
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        let closed = seen
+            .iter()
+            .filter(|(_, it)| now.duration_since(it.start) >= self.window)
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+        let ended = closed
+            .into_iter()
+            .filter_map(|k| seen.remove(&k))
+            .filter(|it| it.suppressed > 0)
+            .collect();
+
+        let metadata = event.metadata();
+        if *metadata.level() != Level::ERROR || std::ptr::eq(metadata, &DUPLICATES) {
+            return (false, ended);
+        }
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        let key = HASHER.hash_one((metadata.target(), &message.0));
+        match seen.get_mut(&key) {
+            Some(burst) => {
+                burst.suppressed += 1;
+                (true, ended)
+            }
+            None => {
+                seen.insert(
+                    key,
+                    Burst {
+                        start: now,
+                        target: metadata.target().into(),
+                        message: message.0,
+                        suppressed: 0,
+                    },
+                );
+                (false, ended)
+            }
+        }
+    }
+    /// Final lines for the log reporting bursts that are still open,
+    /// in the same style as [`LevelCounts::summary`].
+    pub(crate) fn pending(&self, json: bool) -> Vec<String> {
+        let quote = |s: &str| {
+            #[cfg(feature = "serde_json1")]
+            {
+                serde_json::to_string(s).unwrap_or_default()
+            }
+            #[cfg(not(feature = "serde_json1"))]
+            {
+                format!("{s:?}")
+            }
+        };
+        let mut open = self
+            .seen
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drain()
+            .map(|(_, it)| it)
+            .filter(|it| it.suppressed > 0)
+            .collect::<Vec<_>>();
+        open.sort_by_key(|it| it.start);
+        open.into_iter()
+            .map(|it| {
+                let (error, target) = (quote(&it.message), quote(&it.target));
+                match json {
+                    true => format!(
+                        concat!(
+                            r#"{{"message":"suppressed duplicate errors","#,
+                            r#""error":{},"error.target":{},"repeated":{}}}"#
+                        ),
+                        error, target, it.suppressed
+                    ),
+                    false => format!(
+                        "suppressed duplicate errors error={} error.target={} repeated={}",
+                        error, target, it.suppressed
+                    ),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Records the `message` field of an event.
+struct Message(String);
+
+impl Visit for Message {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = value.into()
+        }
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{value:?}")
+        }
+    }
+}
+
+/// Renders JSON with colors like `jq`, see [`crate::Json::colorize`].
+#[cfg(feature = "serde_json1")]
+struct Colorized<'a>(&'a json::Value);
+
+#[cfg(feature = "serde_json1")]
+impl fmt::Display for Colorized<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use json::Value;
+        match self.0 {
+            Value::Null => write!(f, "{}", Color::DarkGray.normal().bold().paint("null")),
+            Value::Bool(it) => write!(f, "{}", Color::Yellow.normal().paint(it.to_string())),
+            Value::Number(it) => write!(f, "{}", Color::Cyan.normal().paint(it.to_string())),
+            Value::String(_) => write!(f, "{}", Color::Green.normal().paint(self.0.to_string())),
+            Value::Array(it) => {
+                f.write_str("[")?;
+                for (ix, it) in it.iter().enumerate() {
+                    if ix != 0 {
+                        f.write_str(",")?
+                    }
+                    write!(f, "{}", Colorized(it))?
+                }
+                f.write_str("]")
+            }
+            Value::Object(it) => {
+                f.write_str("{")?;
+                for (ix, (k, v)) in it.iter().enumerate() {
+                    if ix != 0 {
+                        f.write_str(",")?
+                    }
+                    let mut key = String::new();
+                    json::write_str(&mut key, k)?;
+                    write!(
+                        f,
+                        "{}:{}",
+                        Color::Blue.normal().bold().paint(key),
+                        Colorized(v)
+                    )?
+                }
+                f.write_str("}")
+            }
+        }
     }
 }
 
-impl<S, N> tracing_subscriber::fmt::FormatEvent<S, N> for FormatEvent
-where
-    S: Subscriber + for<'a> LookupSpan<'a>,
-    N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
-{
-    fn format_event(
-        &self,
-        ctx: &FmtContext<'_, S, N>,
-        writer: Writer<'_>,
-        event: &Event<'_>,
-    ) -> std::fmt::Result {
-        self.0.format_event(ctx, writer, event)
-    }
+/// Implementor of [`tracing_subscriber::fmt::FormatFields`],
+/// constructed [`From`] [`Format`](crate::Format) or [`Formatter`](crate::Formatter).
+pub struct FormatFields {
+    inner: FormatFieldsInner,
+    humanize: BTreeMap<String, crate::Unit>,
+    span_timing: Option<crate::SpanEvents>,
+    /// Fields that always lose to [`crate::Format::constant_fields`].
+    shadowed: BTreeSet<String>,
+    error_chain: bool,
+    max_message_len: Option<usize>,
+    fields_before_message: bool,
+    /// Write the message as `key="..."`,
+    /// see [`crate::Format::message_key`] and [`crate::Logfmt::message_key`].
+    message_key: Option<String>,
+    field_case: Option<crate::FieldCase>,
 }
 
-/// Implementor of [`tracing_subscriber::fmt::FormatFields`], constructed [`From`] [`Formatter`](crate::Formatter).
-pub struct FormatFields(FormatFieldsInner);
+/// See [`FormatFields::message_key`].
+fn message_key(formatter: &crate::Formatter, key: Option<String>) -> Option<String> {
+    match formatter {
+        crate::Formatter::Logfmt(it) => Some(
+            it.as_ref()
+                .and_then(|it| it.message_key.clone())
+                .or(key)
+                .unwrap_or_else(|| String::from("msg")),
+        ),
+        _ => key,
+    }
+}
 
 impl From<crate::Formatter> for FormatFields {
     fn from(value: crate::Formatter) -> Self {
-        Self(value.into())
+        Self {
+            message_key: message_key(&value, None),
+            inner: value.into(),
+            humanize: BTreeMap::new(),
+            span_timing: None,
+            shadowed: BTreeSet::new(),
+            error_chain: false,
+            max_message_len: None,
+            fields_before_message: false,
+            field_case: None,
+        }
+    }
+}
+
+impl From<crate::Format> for FormatFields {
+    fn from(value: crate::Format) -> Self {
+        let crate::Format {
+            ansi: _,
+            target: _,
+            level: _,
+            thread_ids: _,
+            thread_names: _,
+            file: _,
+            line_number: _,
+            formatter,
+            timer: _,
+            span_events: _,
+            humanize_fields,
+            span_timing,
+            os_thread_id: _,
+            shutdown_summary: _,
+            constant_fields,
+            field_precedence,
+            error_chain,
+            env_logger_compat: _,
+            timestamp_precision_by_level: _,
+            max_message_len,
+            error_cooldown: _,
+            fields_before_message,
+            hide_default_target: _,
+            level_icons: _,
+            span_id: _,
+            field_case,
+            collapse_repeats: _,
+            level_padding: _,
+            event_id: _,
+            relative_time: _,
+            thread_combined: _,
+            parent_span_name: _,
+            message_key: key,
+            uptime_field: _,
+        } = value;
+        let formatter = formatter.unwrap_or_default();
+        Self {
+            message_key: message_key(&formatter, key),
+            inner: formatter.into(),
+            humanize: humanize_fields.unwrap_or_default(),
+            span_timing,
+            shadowed: match field_precedence {
+                Some(crate::FieldPrecedence::Constant) => {
+                    constant_fields.unwrap_or_default().into_keys().collect()
+                }
+                _ => BTreeSet::new(),
+            },
+            error_chain: error_chain.unwrap_or_default(),
+            max_message_len,
+            fields_before_message: fields_before_message.unwrap_or_default(),
+            field_case: field_case.filter(|it| *it != crate::FieldCase::AsIs),
+        }
     }
 }
 
 impl<'writer> tracing_subscriber::fmt::FormatFields<'writer> for FormatFields {
-    fn format_fields<R: tracing_subscriber::field::RecordFields>(
+    fn format_fields<R: RecordFields>(
         &self,
         writer: Writer<'writer>,
         fields: R,
     ) -> std::fmt::Result {
-        self.0.format_fields(writer, fields)
+        let ansi = writer.has_ansi_escapes();
+        match &self.inner {
+            FormatFieldsInner::Default(_) => {
+                self.record(DefaultVisitor::new(writer, true), ansi, fields)
+            }
+            FormatFieldsInner::Logfmt(_) => {
+                self.record(Quoting(DefaultVisitor::new(writer, true)), ansi, fields)
+            }
+            FormatFieldsInner::Pretty(_) => {
+                self.record(PrettyVisitor::new(writer, true), ansi, fields)
+            }
+            FormatFieldsInner::Json(_) => self.inner.format_fields(writer, fields),
+        }
+    }
+}
+
+impl FormatFields {
+    fn record<V, R>(&self, inner: V, ansi: bool, fields: R) -> fmt::Result
+    where
+        V: Visit + VisitFmt + VisitOutput<fmt::Result>,
+        R: RecordFields,
+    {
+        let mut visitor = Visitor {
+            inner: Renaming {
+                inner,
+                case: self.field_case,
+            },
+            this: self,
+            ansi,
+            is_empty: true,
+            message: None,
+        };
+        fields.record(&mut visitor);
+        let no_fields = visitor.is_empty;
+        for (name, value) in EXTRA_FIELDS.take() {
+            visitor.record_extra(&name, &value)?
+        }
+        if let Some((field, message)) = visitor.message.take() {
+            // `inner` only pads fields it has written itself
+            if no_fields && !visitor.is_empty {
+                visitor.inner.writer().write_char(' ')?
+            }
+            visitor.record_message(&field, &message)
+        }
+        visitor.inner.finish()
+    }
+}
+
+/// Wraps the visitors of the text formatters,
+/// intercepting the fields that we render ourselves.
+struct Visitor<'a, V> {
+    inner: V,
+    this: &'a FormatFields,
+    ansi: bool,
+    is_empty: bool,
+    /// Held back until the end, see [`crate::Format::fields_before_message`].
+    message: Option<(Field, String)>,
+}
+
+impl<V: VisitFmt> Visitor<'_, V> {
+    /// Render a field like [`DefaultVisitor`] would.
+    fn record_extra(&mut self, name: &str, value: &Scalar) -> fmt::Result {
+        let (italic, dimmed) = match self.ansi {
+            true => (Style::new().italic(), Style::new().dimmed()),
+            false => (Style::new(), Style::new()),
+        };
+        let writer = self.inner.writer();
+        if !std::mem::replace(&mut self.is_empty, false) {
+            writer.write_char(' ')?
+        }
+        write!(writer, "{}{}", italic.paint(name), dimmed.paint("="))?;
+        match value {
+            Scalar::String(it) => write!(writer, "{it:?}"),
+            Scalar::U64(it) => write!(writer, "{it}"),
+            Scalar::F64(it) => write!(writer, "{it}"),
+        }
+    }
+}
+
+impl<V: Visit> Visitor<'_, V> {
+    /// Whether this field lost to another source, see [`crate::FieldPrecedence`].
+    fn is_shadowed(&self, field: &Field) -> bool {
+        self.this.shadowed.contains(field.name())
+            || SHADOWED_FIELDS.with_borrow(|it| it.contains(field.name()))
+    }
+    /// Record the `message` field, applying [`crate::Format::max_message_len`]
+    /// and [`crate::Format::message_key`].
+    fn record_message(&mut self, field: &Field, message: &str) {
+        let message = match self.this.max_message_len {
+            Some(max) => truncate(message, max),
+            None => Cow::Borrowed(message),
+        };
+        match &self.this.message_key {
+            Some(key) => self
+                .inner
+                .record_debug(field, &format_args!("{key}={message:?}")),
+            None => self.inner.record_debug(field, &format_args!("{message}")),
+        }
+    }
+    /// Whether the message needs rewriting by [`Self::record_message`].
+    fn rewrites_message(&self, field: &Field) -> bool {
+        field.name() == "message"
+            && (self.this.max_message_len.is_some() || self.this.message_key.is_some())
+    }
+    /// Hold back the message, returning whether it was.
+    fn defer_message(&mut self, field: &Field, value: impl FnOnce() -> String) -> bool {
+        let defer = self.this.fields_before_message && field.name() == "message";
+        if defer {
+            self.message = Some((field.clone(), value()))
+        }
+        defer
+    }
+    fn record_number(&mut self, field: &Field, value: f64, or: impl FnOnce(&mut V)) {
+        self.is_empty = false;
+        match self.this.humanize.get(field.name()) {
+            Some(unit) => self
+                .inner
+                .record_debug(field, &format_args!("{}", Humanized(*unit, value))),
+            None => or(&mut self.inner),
+        }
+    }
+}
+
+impl<V: Visit> Visit for Visitor<'_, V> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if self.is_shadowed(field) {
+            return;
+        }
+        self.record_number(field, value, |it| it.record_f64(field, value))
+    }
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if self.is_shadowed(field) {
+            return;
+        }
+        self.record_number(field, value as f64, |it| it.record_i64(field, value))
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if self.is_shadowed(field) {
+            return;
+        }
+        self.record_number(field, value as f64, |it| it.record_u64(field, value))
+    }
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        if self.is_shadowed(field) {
+            return;
+        }
+        self.is_empty = false;
+        self.inner.record_i128(field, value)
+    }
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        if self.is_shadowed(field) {
+            return;
+        }
+        self.is_empty = false;
+        self.inner.record_u128(field, value)
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if self.is_shadowed(field) {
+            return;
+        }
+        self.is_empty = false;
+        self.inner.record_bool(field, value)
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if self.is_shadowed(field) || self.defer_message(field, || value.into()) {
+            return;
+        }
+        self.is_empty = false;
+        match self.rewrites_message(field) {
+            true => self.record_message(field, value),
+            false => self.inner.record_str(field, value),
+        }
+    }
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        if self.is_shadowed(field) {
+            return;
+        }
+        self.is_empty = false;
+        match self.this.error_chain {
+            true => self
+                .inner
+                .record_debug(field, &format_args!("{}", ErrorChain(value))),
+            false => self.inner.record_error(field, value),
+        }
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.is_shadowed(field) || self.defer_message(field, || format!("{value:?}")) {
+            return;
+        }
+        // measured by `FormatEvent`, rather than parsed from `value`
+        let timing = match (&self.this.span_timing, field.name(), SPAN_TIMINGS.get()) {
+            (Some(_), "time.busy" | "time.idle", Some(None)) => return,
+            (Some(it), "time.busy", Some(Some((busy, _)))) => Some((it, busy)),
+            (Some(it), "time.idle", Some(Some((_, idle)))) => Some((it, idle)),
+            _ => None,
+        };
+        self.is_empty = false;
+        if self.rewrites_message(field) {
+            return self.record_message(field, &format!("{value:?}"));
+        }
+        match timing {
+            Some((timing, it)) => self.inner.record_debug(
+                field,
+                &format_args!("{}", Timing(timing.unit.unwrap_or_default(), it)),
+            ),
+            None => self.inner.record_debug(field, value),
+        }
+    }
+}
+
+/// A duration rendered in a [`TimingUnit`](crate::TimingUnit).
+struct Timing(crate::TimingUnit, Duration);
+
+impl fmt::Display for Timing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(unit, it) = *self;
+        match unit {
+            crate::TimingUnit::Nanoseconds => write!(f, "{}ns", it.as_nanos()),
+            crate::TimingUnit::Milliseconds => write!(f, "{}ms", it.as_secs_f64() * 1e3),
+            // like `tracing_subscriber`
+            crate::TimingUnit::Human => {
+                let mut n = it.as_nanos() as f64;
+                for unit in ["ns", "µs", "ms", "s"] {
+                    match n {
+                        ..10.0 => return write!(f, "{n:.2}{unit}"),
+                        ..100.0 => return write!(f, "{n:.1}{unit}"),
+                        ..1000.0 => return write!(f, "{n:.0}{unit}"),
+                        _ => n /= 1000.0,
+                    }
+                }
+                write!(f, "{:.0}s", n * 1000.0)
+            }
+        }
+    }
+}
+
+/// A number rendered in [`Unit`](crate::Unit)s.
+struct Humanized(crate::Unit, f64);
+
+impl fmt::Display for Humanized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(unit, n) = *self;
+        match unit {
+            crate::Unit::Bytes => {
+                const UNITS: [&str; 6] = ["B", "KB", "MB", "GB", "TB", "PB"];
+                let (mut n, mut ix) = (n, 0);
+                while n.abs() >= 1024.0 && ix < UNITS.len() - 1 {
+                    n /= 1024.0;
+                    ix += 1;
+                }
+                match ix {
+                    0 => write!(f, "{n} {}", UNITS[ix]),
+                    _ => write!(f, "{n:.1} {}", UNITS[ix]),
+                }
+            }
+            crate::Unit::Duration => match n.abs() {
+                ..1e3 => write!(f, "{n}ns"),
+                ..1e6 => write!(f, "{:.1}µs", n / 1e3),
+                ..1e9 => write!(f, "{:.1}ms", n / 1e6),
+                _ => write!(f, "{:.1}s", n / 1e9),
+            },
+        }
     }
 }
 
@@ -59,10 +1943,14 @@ enum FormatEventInner {
     Compact(Format<Compact, FormatTime>),
     Pretty(Format<Pretty, FormatTime>),
     Json(Format<Json, FormatTime>),
+    EnvLogger(EnvLogger),
+    Logfmt(Logfmt),
+    Minimal(Minimal),
 }
 
-impl From<crate::Format> for FormatEventInner {
-    fn from(value: crate::Format) -> Self {
+impl FormatEventInner {
+    /// Shows the time relative to `previous` if given, see [`crate::Format::relative_time`].
+    fn new(value: crate::Format, previous: Option<crate::time::Previous>) -> Self {
         let crate::Format {
             ansi,
             target,
@@ -73,10 +1961,41 @@ impl From<crate::Format> for FormatEventInner {
             line_number,
             formatter,
             timer,
+            env_logger_compat,
+            timestamp_precision_by_level,
+            max_message_len: _,
+            error_cooldown: _,
+            fields_before_message: _,
+            hide_default_target: _,
+            level_icons: _,
+            span_id: _,
+            field_case: _,
+            collapse_repeats: _,
+            level_padding: _,
+            event_id: _,
+            relative_time: _, // see `previous`
+            thread_combined: _,
+            parent_span_name: _,
+            message_key: _,
+            uptime_field: _,
             span_events: _, // handled out-of-band
+            humanize_fields: _,
+            span_timing: _,
+            os_thread_id: _,
+            shutdown_summary: _,
+            constant_fields: _,
+            field_precedence: _,
+            error_chain: _,
         } = value;
 
-        let orig = Format::default().with_timer(FormatTime::from(timer.unwrap_or_default()));
+        let timer = timer.unwrap_or_default();
+        let precision_by_level = timestamp_precision_by_level.unwrap_or_default();
+        let format_time = || {
+            FormatTime::from(timer.clone())
+                .with_precision_by_level(precision_by_level.clone())
+                .with_relative_time(previous.clone())
+        };
+        let orig = Format::default().with_timer(format_time());
         let mut this = match formatter.unwrap_or_default() {
             crate::Formatter::Full => Self::Full(orig),
             crate::Formatter::Compact => Self::Compact(orig.compact()),
@@ -86,6 +2005,11 @@ impl From<crate::Format> for FormatEventInner {
                     flatten_event,
                     current_span,
                     span_list,
+                    record_separator: _,
+                    colorize: _,
+                    typed_values: _,
+                    message_key: _,
+                    span_records: _,
                 } = it.unwrap_or_default();
                 let mut this = orig.json();
                 if let Some(it) = flatten_event {
@@ -99,6 +2023,14 @@ impl From<crate::Format> for FormatEventInner {
                 }
                 this
             }),
+            crate::Formatter::Logfmt(_) => Self::Logfmt(Logfmt {
+                timer: format_time(),
+                target: target.unwrap_or(true),
+                level: level.unwrap_or(true),
+            }),
+            crate::Formatter::Minimal => Self::Minimal(Minimal {
+                level: level.unwrap_or(true),
+            }),
         };
 
         macro_rules! apply {
@@ -109,11 +2041,19 @@ impl From<crate::Format> for FormatEventInner {
                         Self::Compact(it) => Self::Compact(it.$method(arg)),
                         Self::Pretty(it) => Self::Pretty(it.$method(arg)),
                         Self::Json(it) => Self::Json(it.$method(arg)),
+                        Self::EnvLogger(it) => Self::EnvLogger(it),
+                        Self::Logfmt(it) => Self::Logfmt(it),
+                        Self::Minimal(it) => Self::Minimal(it),
                     };
                 }
             };
         }
 
+        // escapes would corrupt JSON
+        let ansi = match this {
+            Self::Json(_) => Some(false),
+            _ => ansi,
+        };
         apply!(this.with_ansi(ansi));
         apply!(this.with_target(target));
         apply!(this.with_level(level));
@@ -122,7 +2062,156 @@ impl From<crate::Format> for FormatEventInner {
         apply!(this.with_file(file));
         apply!(this.with_line_number(line_number));
 
-        this
+        match (this, env_logger_compat) {
+            (Self::Full(_) | Self::Compact(_), Some(true)) => Self::EnvLogger(EnvLogger {
+                ansi,
+                timer: format_time(),
+                target: target.unwrap_or(true),
+                level: level.unwrap_or(true),
+            }),
+            (this, _) => this,
+        }
+    }
+}
+
+/// Lays events out like `env_logger`, see [`crate::Format::env_logger_compat`].
+struct EnvLogger {
+    ansi: Option<bool>,
+    timer: FormatTime,
+    target: bool,
+    level: bool,
+}
+
+impl EnvLogger {
+    fn format_event<S, N>(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+    {
+        match self.ansi.unwrap_or(writer.has_ansi_escapes()) {
+            true => self.write(ctx, writer, true, event),
+            false => {
+                // `Writer::new` never has ANSI escapes
+                let mut buf = String::new();
+                self.write(ctx, Writer::new(&mut buf), false, event)?;
+                writer.write_str(&buf)
+            }
+        }
+    }
+    fn write<S, N>(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        ansi: bool,
+        event: &Event<'_>,
+    ) -> fmt::Result
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+    {
+        let style = |it: Style| match ansi {
+            true => it,
+            false => Style::new(),
+        };
+        let subtle = style(Color::DarkGray.normal());
+        let mut header = vec![];
+        let mut time = String::new();
+        self.timer.format_time(&mut Writer::new(&mut time))?;
+        if !time.is_empty() {
+            header.push(time)
+        }
+        if self.level {
+            let level = *event.metadata().level();
+            let color = match level {
+                Level::TRACE => Color::Cyan,
+                Level::DEBUG => Color::Blue,
+                Level::INFO => Color::Green,
+                Level::WARN => Color::Yellow,
+                Level::ERROR => Color::Red,
+            };
+            header.push(
+                style(color.normal())
+                    .paint(format!("{:<5}", level.as_str()))
+                    .to_string(),
+            )
+        }
+        if self.target {
+            header.push(event.metadata().target().into())
+        }
+        write!(
+            writer,
+            "{}{}{} ",
+            subtle.paint("["),
+            header.join(" "),
+            subtle.paint("]")
+        )?;
+        ctx.format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// See [`crate::Formatter::Logfmt`].
+struct Logfmt {
+    timer: FormatTime,
+    target: bool,
+    level: bool,
+}
+
+impl Logfmt {
+    fn format_event<S, N>(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+    {
+        let mut pairs = vec![];
+        let mut time = String::new();
+        self.timer.format_time(&mut Writer::new(&mut time))?;
+        if !time.is_empty() {
+            pairs.push(("time", time))
+        }
+        if self.level {
+            pairs.push(("level", event.metadata().level().as_str().to_lowercase()))
+        }
+        if self.target {
+            pairs.push(("target", event.metadata().target().into()))
+        }
+        for (key, value) in pairs {
+            match needs_quotes(&value) {
+                true => write!(writer, "{key}={value:?} ")?,
+                false => write!(writer, "{key}={value} ")?,
+            }
+        }
+        // `Writer::new` never has ANSI escapes
+        let mut fields = String::new();
+        ctx.format_fields(Writer::new(&mut fields), event)?;
+        writeln!(writer, "{fields}")
+    }
+}
+
+/// See [`crate::Formatter::Minimal`].
+struct Minimal {
+    level: bool,
+}
+
+impl Minimal {
+    fn format_event(&self, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        match (self.level, message.0.is_empty()) {
+            (true, true) => writeln!(writer, "{}", event.metadata().level()),
+            (true, false) => writeln!(writer, "{} {}", event.metadata().level(), message.0),
+            (false, _) => writeln!(writer, "{}", message.0),
+        }
     }
 }
 
@@ -142,12 +2231,17 @@ where
             FormatEventInner::Compact(it) => it.format_event(ctx, writer, event),
             FormatEventInner::Pretty(it) => it.format_event(ctx, writer, event),
             FormatEventInner::Json(it) => it.format_event(ctx, writer, event),
+            FormatEventInner::EnvLogger(it) => it.format_event(ctx, writer, event),
+            FormatEventInner::Logfmt(it) => it.format_event(ctx, writer, event),
+            FormatEventInner::Minimal(it) => it.format_event(writer, event),
         }
     }
 }
 
 enum FormatFieldsInner {
     Default(DefaultFields),
+    /// Rendered with [`Quoting`].
+    Logfmt(DefaultFields),
     Json(JsonFields),
     Pretty(PrettyFields),
 }
@@ -159,6 +2253,8 @@ impl From<crate::Formatter> for FormatFieldsInner {
             crate::Formatter::Compact => Self::Default(DefaultFields::new()),
             crate::Formatter::Pretty => Self::Pretty(PrettyFields::new()),
             crate::Formatter::Json { .. } => Self::Json(JsonFields::new()),
+            crate::Formatter::Logfmt(_) => Self::Logfmt(DefaultFields::new()),
+            crate::Formatter::Minimal => Self::Default(DefaultFields::new()),
         }
     }
 }
@@ -171,8 +2267,1024 @@ impl<'writer> tracing_subscriber::fmt::FormatFields<'writer> for FormatFieldsInn
     ) -> std::fmt::Result {
         match self {
             FormatFieldsInner::Default(it) => it.format_fields(writer, fields),
+            FormatFieldsInner::Logfmt(it) => it.format_fields(writer, fields),
             FormatFieldsInner::Json(it) => it.format_fields(writer, fields),
             FormatFieldsInner::Pretty(it) => it.format_fields(writer, fields),
         }
     }
 }
+
+#[cfg(test)]
+#[test]
+fn humanize() {
+    let format = crate::Format {
+        humanize_fields: Some(BTreeMap::from_iter([
+            (String::from("bytes"), crate::Unit::Bytes),
+            (String::from("elapsed"), crate::Unit::Duration),
+        ])),
+        ..crate::plain()
+    };
+    let subscriber = crate::Subscriber {
+        format: Some(format),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        tracing::info!(bytes = 1572864, elapsed = 2_300_000_000u64, other = 1572864)
+    });
+    assert!(
+        s.ends_with("bytes=1.5 MB elapsed=2.3s other=1572864\n"),
+        "{s}"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn span_timing() {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    let format = crate::Format {
+        span_events: Some(FmtSpan::CLOSE),
+        span_timing: Some(crate::SpanEvents {
+            min_duration: Some(std::time::Duration::from_millis(50)),
+            unit: Some(crate::TimingUnit::Milliseconds),
+        }),
+        ..crate::plain()
+    };
+    let subscriber = crate::Subscriber {
+        format: Some(format),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        drop(tracing::info_span!("short").entered());
+        let _long = tracing::info_span!("long").entered();
+        std::thread::sleep(std::time::Duration::from_millis(60));
+    });
+    let [short, long] = s.lines().collect::<Vec<_>>()[..] else {
+        panic!("{s}")
+    };
+    assert!(short.ends_with("close"), "{short}");
+    assert!(
+        long.contains("close time.busy=") && long.ends_with("ms"),
+        "{long}"
+    );
+
+    // the events used for timing are only written if requested
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            span_events: Some(FmtSpan::NEW | FmtSpan::CLOSE),
+            span_timing: Some(crate::SpanEvents {
+                min_duration: None,
+                unit: Some(crate::TimingUnit::Nanoseconds),
+            }),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        drop(tracing::info_span!("span").entered());
+    });
+    let [new, close] = s.lines().collect::<Vec<_>>()[..] else {
+        panic!("{s}")
+    };
+    assert!(new.ends_with("new"), "{new}");
+    assert!(
+        close.contains("time.busy=") && close.ends_with("ns"),
+        "{close}"
+    );
+}
+
+#[cfg(all(
+    test,
+    feature = "libc0_2",
+    any(target_os = "linux", target_vendor = "apple")
+))]
+#[test]
+fn os_tid() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            os_thread_id: Some(true),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        tracing::info!("main");
+        let dispatch = tracing::dispatcher::get_default(Clone::clone);
+        std::thread::spawn(move || {
+            tracing::dispatcher::with_default(&dispatch, || tracing::info!("spawned"))
+        })
+        .join()
+        .unwrap();
+    });
+    let tids = s
+        .lines()
+        .map(|it| {
+            let (_, tid) = it.split_once(" os_tid=").unwrap();
+            tid.parse::<u64>().unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert!(matches!(tids[..], [l, r] if l != r), "{s}");
+}
+
+#[cfg(test)]
+#[test]
+fn shutdown_summary() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (builder, guard) = crate::Subscriber {
+        format: Some(crate::plain()),
+        writer: Some(crate::Writer::File(crate::File {
+            path: path.clone().into(),
+            non_blocking: Some(crate::NonBlocking::default()),
+            ..Default::default()
+        })),
+        filter: Some(crate::Filter {
+            directives: vec!["info".parse().unwrap()],
+            ..Default::default()
+        }),
+        log_bridge: None,
+        total_byte_budget: None,
+    }
+    .with_shutdown_summary()
+    .builder();
+    tracing::subscriber::with_default(builder.finish(), || {
+        tracing::info!("one");
+        tracing::info!("two");
+        tracing::warn!("three");
+        tracing::debug!("filtered");
+    });
+    drop(guard);
+    let s = std::fs::read_to_string(path).unwrap();
+    assert_eq!(
+        s.lines().last().unwrap(),
+        "shutdown summary: TRACE=0 DEBUG=0 INFO=2 WARN=1 ERROR=0"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn field_precedence() {
+    let run = |field_precedence| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                constant_fields: Some(BTreeMap::from_iter([(
+                    String::from("region"),
+                    String::from("eu"),
+                )])),
+                field_precedence,
+                target: Some(false),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || {
+            let _span = tracing::info_span!("request", region = "us").entered();
+            tracing::info!("hello")
+        })
+    };
+    assert_eq!(
+        run(Some(crate::FieldPrecedence::Constant)),
+        "  INFO request: hello region=\"eu\"\n"
+    );
+    let span = run(Some(crate::FieldPrecedence::Span));
+    assert!(span.contains("\"us\"") && !span.contains("eu"), "{span}");
+    assert_eq!(run(None), run(Some(crate::FieldPrecedence::Event)));
+}
+
+#[cfg(all(test, feature = "serde_json1"))]
+#[test]
+fn error_chain() {
+    #[derive(Debug)]
+    struct Outer(std::io::Error);
+    impl fmt::Display for Outer {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("couldn't load config")
+        }
+    }
+    impl std::error::Error for Outer {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+    let run = |formatter| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                error_chain: Some(true),
+                formatter: Some(formatter),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || {
+            let e = Outer(std::io::Error::other("file not found"));
+            tracing::error!(error = &e as &dyn std::error::Error)
+        })
+    };
+    let s = run(crate::Formatter::Full);
+    assert!(
+        s.ends_with("error=couldn't load config; caused by: file not found\n"),
+        "{s}"
+    );
+    let s = run(crate::Formatter::Json(None));
+    assert!(
+        s.contains(r#""error":["couldn't load config","file not found"]"#),
+        "{s}"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn env_logger_compat() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            env_logger_compat: Some(true),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        let _span = tracing::info_span!("omitted").entered();
+        tracing::info!(answer = 42, "hello")
+    });
+    assert_eq!(s, "[INFO  tracing_configuration::format] hello answer=42\n");
+}
+
+#[cfg(all(test, feature = "serde_json1"))]
+#[test]
+fn max_message_len() {
+    let run = |formatter| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                max_message_len: Some(5),
+                formatter: Some(formatter),
+                target: Some(false),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || {
+            tracing::info!(field = "ünïcödé field", "ünïcödé message")
+        })
+    };
+    assert_eq!(
+        run(crate::Formatter::Full),
+        "  INFO ünïcö… field=\"ünïcödé field\"\n"
+    );
+    let s = run(crate::Formatter::Json(None));
+    assert!(
+        s.contains(r#""message":"ünïcö…","field":"ünïcödé field""#),
+        "{s}"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn fields_before_message() {
+    let run = |fields_before_message| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                fields_before_message: Some(fields_before_message),
+                target: Some(false),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || {
+            tracing::info!(user = "alice", attempt = 2, "logged in")
+        })
+    };
+    assert_eq!(run(false), "  INFO logged in user=\"alice\" attempt=2\n");
+    assert_eq!(run(true), "  INFO user=\"alice\" attempt=2 logged in\n");
+}
+
+#[cfg(test)]
+#[test]
+fn logfmt() {
+    let run = |message_key: Option<&str>| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                formatter: Some(crate::Formatter::Logfmt(Some(crate::Logfmt {
+                    message_key: message_key.map(Into::into),
+                }))),
+                ansi: Some(true),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || tracing::info!(user = "alice", "hello"))
+    };
+    assert_eq!(
+        run(None),
+        "level=info target=tracing_configuration::format msg=\"hello\" user=\"alice\"\n"
+    );
+    #[derive(Debug)]
+    #[expect(dead_code, reason = "only read by `Debug`")]
+    struct Point {
+        x: i32,
+        label: &'static str,
+    }
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            formatter: Some(crate::Formatter::Logfmt(None)),
+            target: Some(false),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(
+        subscriber,
+        || tracing::info!(point = ?Point { x: 1, label: "a" }, id = ?"a=b", plain = %"ok", "hi"),
+    );
+    assert_eq!(
+        s,
+        "level=info msg=\"hi\" point=\"Point { x: 1, label: \\\"a\\\" }\" id=\"\\\"a=b\\\"\" plain=ok\n"
+    );
+    assert_eq!(
+        run(Some("message")),
+        "level=info target=tracing_configuration::format message=\"hello\" user=\"alice\"\n"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn text_message_key() {
+    let run = |formatter| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                message_key: Some(String::from("msg")),
+                formatter: Some(formatter),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || tracing::info!(user = "alice", "hello world"))
+    };
+    assert_eq!(
+        run(crate::Formatter::Full),
+        "  INFO tracing_configuration::format: msg=\"hello world\" user=\"alice\"\n"
+    );
+    assert!(run(crate::Formatter::Compact).contains(" msg=\"hello world\" user=\"alice\""));
+    assert_eq!(
+        run(crate::Formatter::Logfmt(None)),
+        "level=info target=tracing_configuration::format msg=\"hello world\" user=\"alice\"\n"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn hide_default_target() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            hide_default_target: Some(true),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        tracing::info!(target: "my_app", "bare");
+        tracing::info!(target: "my_app::db", "qualified");
+    });
+    assert_eq!(
+        s.lines().collect::<Vec<_>>(),
+        ["  INFO bare", "  INFO my_app::db: qualified"]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn level_icons() {
+    let run = |icons: crate::LevelIcons| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                level_icons: Some(icons),
+                target: Some(false),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || {
+            tracing::error!("bad");
+            tracing::info!("good");
+        })
+    };
+    assert_eq!(run(Default::default()), " 🔴 bad\n 🔵 good\n");
+    assert_eq!(
+        run(crate::LevelIcons {
+            icons: Some(BTreeMap::from_iter([(
+                crate::Level::Info,
+                String::from("i")
+            )])),
+            label: Some(true),
+        }),
+        " 🔴 ERROR bad\n i   INFO good\n"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn colored_icons_align() {
+    let run = |error: &str| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                level_icons: Some(crate::LevelIcons {
+                    icons: Some(BTreeMap::from_iter([
+                        (crate::Level::Error, String::from(error)),
+                        (crate::Level::Info, String::from("i")),
+                    ])),
+                    label: None,
+                }),
+                target: Some(false),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        let s = crate::capture(subscriber, || {
+            tracing::error!("bad");
+            tracing::info!("good");
+        });
+        strip_ansi(&s).into_owned()
+    };
+    assert_eq!(run("\x1b[31mE\x1b[0m"), run("E"));
+    assert_eq!(run("E"), " E  bad\n i  good\n");
+}
+
+#[cfg(test)]
+#[test]
+fn level_padding() {
+    let run = |level_padding| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                level_padding,
+                target: Some(false),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || {
+            tracing::trace!("a");
+            tracing::warn!("b");
+        })
+    };
+    assert_eq!(run(Some(5)), run(None));
+    assert_eq!(run(Some(7)), "   TRACE a\n    WARN b\n");
+    assert_eq!(run(Some(5)), " TRACE a\n  WARN b\n");
+    assert_eq!(run(Some(0)), " TRACE a\n WARN b\n");
+}
+
+#[cfg(test)]
+#[test]
+fn record_separator() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            formatter: Some(crate::Formatter::Json(Some(crate::Json {
+                record_separator: Some(crate::RecordSeparator::Rs),
+                ..Default::default()
+            }))),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        tracing::info!("one");
+        tracing::info!("two");
+    });
+    let records = s.split_terminator('\n').collect::<Vec<_>>();
+    assert_eq!(records.len(), 2);
+    for record in records {
+        let json = record.strip_prefix('\x1e').unwrap();
+        serde_json::from_str::<serde_json::Value>(json).unwrap();
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn json_never_ansi() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            ansi: Some(true),
+            formatter: Some(crate::Formatter::Json(None)),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        let _span = tracing::info_span!("span", answer = 42).entered();
+        tracing::warn!(field = true, "hello")
+    });
+    assert!(!s.contains('\x1b'), "{s}");
+    serde_json::from_str::<serde_json::Value>(&s).unwrap();
+}
+
+#[cfg(test)]
+#[test]
+fn thread_combined() {
+    let run = |name: Option<&str>| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                thread_ids: Some(true),
+                thread_names: Some(true),
+                thread_combined: Some(true),
+                target: Some(false),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        let mut builder = std::thread::Builder::new();
+        if let Some(name) = name {
+            builder = builder.name(name.into())
+        }
+        builder
+            .spawn(|| {
+                let s = crate::capture(subscriber, || tracing::info!("hello"));
+                (s, std::thread::current().id())
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+    };
+    let (s, id) = run(Some("ThreadPool-1"));
+    let id = format!("{id:?}");
+    let id = id.trim_start_matches("ThreadId(").trim_end_matches(')');
+    assert!(
+        s.ends_with(&format!(" INFO ThreadPool-1({id}) hello\n")),
+        "{s}"
+    );
+    // unnamed threads are written like without `thread_combined`
+    let (s, id) = run(None);
+    assert!(s.ends_with(&format!(" INFO {id:0>2?} hello\n")), "{s}");
+}
+
+#[cfg(test)]
+#[test]
+fn dynamic_fields() {
+    thread_local! {
+        static REQUEST_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+    }
+    let (builder, _guard) = crate::Subscriber {
+        format: Some(crate::Format {
+            target: Some(false),
+            ..crate::plain()
+        }),
+        filter: Some(crate::Filter {
+            directives: vec!["trace".parse().unwrap()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .builder();
+    let builder = builder.map_event_format(|it| {
+        it.with_dynamic_fields(|| {
+            REQUEST_ID.with_borrow(|it| {
+                it.iter()
+                    .map(|it| (String::from("request_id"), it.clone()))
+                    .collect()
+            })
+        })
+    });
+    let s = crate::capture_builder(builder, || {
+        tracing::info!("none");
+        REQUEST_ID.set(Some(String::from("a")));
+        tracing::info!("first");
+        REQUEST_ID.set(Some(String::from("b")));
+        tracing::info!("second");
+    });
+    assert_eq!(
+        s,
+        "  INFO none\n  INFO first request_id=\"a\"\n  INFO second request_id=\"b\"\n"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn event_id() {
+    let run = |kind| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                event_id: Some(kind),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        let s = crate::capture(subscriber, || {
+            tracing::info!("first");
+            tracing::info!("second");
+        });
+        s.lines()
+            .map(|it| it.split_once(" event_id=").unwrap().1.to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(run(crate::EventIdKind::Counter), ["0", "1"]);
+    #[cfg(feature = "uuid1")]
+    assert!(matches!(&run(crate::EventIdKind::Uuid)[..], [l, r] if l != r));
+    #[cfg(feature = "ulid1")]
+    assert!(matches!(&run(crate::EventIdKind::Ulid)[..], [l, r] if l != r));
+}
+
+#[cfg(all(test, feature = "serde_json1"))]
+#[test]
+fn span_id() {
+    let run = |formatter| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                span_id: Some(true),
+                formatter: Some(formatter),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || {
+            tracing::info_span!("request").in_scope(|| {
+                tracing::info!("first");
+                tracing::info!("second");
+            });
+            tracing::info!("outside");
+        })
+    };
+    let s = run(crate::Formatter::Full);
+    let ids = s
+        .lines()
+        .filter_map(|it| it.split_once(" span_id=").map(|(_, id)| id))
+        .collect::<Vec<_>>();
+    assert!(
+        matches!(ids[..], [l, r] if l == r && l.parse::<u64>().is_ok()),
+        "{s}"
+    );
+    let s = run(crate::Formatter::Json(None));
+    let ids = s
+        .lines()
+        .map(|it| serde_json::from_str::<serde_json::Value>(it).unwrap()["span_id"].clone())
+        .collect::<Vec<_>>();
+    assert!(
+        matches!(&ids[..], [l, r, serde_json::Value::Null] if l == r && l.is_u64()),
+        "{s}"
+    );
+}
+
+#[cfg(all(test, feature = "serde_json1"))]
+#[test]
+fn parent_span_name() {
+    let run = |formatter| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                parent_span_name: Some(true),
+                formatter: Some(formatter),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || {
+            tracing::info_span!("outer").in_scope(|| {
+                tracing::info_span!("inner").in_scope(|| tracing::info!("nested"));
+                tracing::info!("shallow");
+            });
+            tracing::info!("outside");
+        })
+    };
+    let s = run(crate::Formatter::Compact);
+    let names = s
+        .lines()
+        .map(|it| it.split_once(" parent_span=").map(|(_, it)| it))
+        .collect::<Vec<_>>();
+    assert_eq!(names, [Some(r#""inner""#), Some(r#""outer""#), None], "{s}");
+    let s = run(crate::Formatter::Json(None));
+    let names = s
+        .lines()
+        .map(|it| serde_json::from_str::<serde_json::Value>(it).unwrap()["parent_span"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            serde_json::json!("inner"),
+            serde_json::json!("outer"),
+            serde_json::Value::Null
+        ],
+        "{s}"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn uptime_field() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            uptime_field: Some(true),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        tracing::info!("first");
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        tracing::info!("second");
+    });
+    let uptimes = s
+        .lines()
+        .map(|it| {
+            let (_, uptime) = it.split_once(" uptime_secs=").unwrap();
+            uptime.parse::<f64>().unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        matches!(uptimes[..], [l, r] if l >= 0.0 && r - l >= 0.015),
+        "{s}"
+    );
+}
+
+#[cfg(all(test, feature = "serde_json1"))]
+#[test]
+fn field_case() {
+    let run = |field_case, formatter| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                field_case: Some(field_case),
+                formatter: Some(formatter),
+                target: Some(false),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || {
+            tracing::info!(requestId = 1, "hello");
+        })
+    };
+    assert_eq!(
+        run(crate::FieldCase::Snake, crate::Formatter::Compact),
+        "  INFO hello request_id=1\n"
+    );
+    assert_eq!(
+        run(crate::FieldCase::AsIs, crate::Formatter::Compact),
+        "  INFO hello requestId=1\n"
+    );
+    let json = run(crate::FieldCase::Snake, crate::Formatter::Json(None));
+    let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+    assert_eq!(json["fields"]["request_id"], 1, "{json}");
+    assert!(json.get("level").is_some(), "{json}");
+
+    let apply = |case: crate::FieldCase, name| case.apply(name);
+    assert_eq!(apply(crate::FieldCase::Camel, "request_id"), "requestId");
+    assert_eq!(
+        apply(crate::FieldCase::Kebab, "HTTPServer.retryCount"),
+        "http-server.retry-count"
+    );
+    assert_eq!(apply(crate::FieldCase::Snake, "message"), "message");
+}
+
+#[cfg(all(test, feature = "serde_json1"))]
+#[test]
+fn typed_values() {
+    let run = |typed_values| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                formatter: Some(crate::Formatter::Json(Some(crate::Json {
+                    typed_values: Some(typed_values),
+                    ..Default::default()
+                }))),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        let s = crate::capture(subscriber, || {
+            tracing::info!(count = 5i64, ok = true, debug = ?5, display = %false, name = %"x", "1");
+            tracing::info!(version = %"1.10", big = %u64::MAX, huge = %u128::MAX, padded = %"05", null = %"null", "2");
+        });
+        s.lines()
+            .map(|it| serde_json::from_str::<serde_json::Value>(it).unwrap()["fields"].clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        run(true),
+        [
+            serde_json::json!({"message":"1","count":5,"ok":true,"debug":5,"display":false,"name":"x"}),
+            serde_json::json!({"message":"2","version":"1.10","big":u64::MAX,"huge":u128::MAX.to_string(),"padded":"05","null":"null"}),
+        ]
+    );
+    assert_eq!(
+        run(false)[0],
+        serde_json::json!({"message":"1","count":5,"ok":true,"debug":"5","display":"false","name":"x"})
+    );
+}
+
+#[cfg(all(test, feature = "serde_json1"))]
+#[test]
+fn json_message_key() {
+    let run = |flatten_event| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                formatter: Some(crate::Formatter::Json(Some(crate::Json {
+                    message_key: Some(String::from("msg")),
+                    flatten_event: Some(flatten_event),
+                    ..Default::default()
+                }))),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        let s = crate::capture(subscriber, || tracing::info!(answer = 42, "hello"));
+        serde_json::from_str::<serde_json::Value>(&s).unwrap()
+    };
+    let nested = run(false);
+    assert_eq!(
+        nested["fields"],
+        serde_json::json!({"msg":"hello","answer":42})
+    );
+    let flat = run(true);
+    assert_eq!(flat["msg"], "hello", "{flat}");
+    assert!(flat.get("message").is_none(), "{flat}");
+}
+
+#[cfg(all(test, feature = "serde_json1"))]
+#[test]
+fn span_records() {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            formatter: Some(crate::Formatter::Json(Some(crate::Json {
+                span_records: Some(true),
+                span_list: Some(true),
+                ..Default::default()
+            }))),
+            span_events: Some(FmtSpan::NEW | FmtSpan::CLOSE),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        let _outer = tracing::info_span!("outer").entered();
+        tracing::info_span!("request", id = 1).in_scope(|| tracing::info!("hello"));
+    });
+    let records = s
+        .lines()
+        .map(|it| serde_json::from_str::<serde_json::Value>(it).unwrap())
+        .collect::<Vec<_>>();
+    let [outer_open, open, hello, close, outer_close] = &records[..] else {
+        panic!("{s}")
+    };
+    assert_eq!(
+        s.lines().nth(1).unwrap(),
+        r#"{"timestamp":"","level":"INFO","event":"span_open","name":"request","target":"tracing_configuration::format","fields":{"id":1},"spans":[{"name":"outer"}]}"#
+    );
+    assert_eq!(open["event"], "span_open");
+    assert_eq!(close["event"], "span_close");
+    assert_eq!(close["fields"]["id"], 1);
+    assert!(close["time.busy"].is_string() && close["time.idle"].is_string());
+    assert_eq!(outer_open["spans"], serde_json::json!([]));
+    assert_eq!(outer_close["name"], "outer");
+    assert_eq!(hello["fields"]["message"], "hello");
+}
+
+#[cfg(test)]
+#[test]
+fn minimal() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            formatter: Some(crate::Formatter::Minimal),
+            timer: Some(crate::Timer::System),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        tracing::info!(answer = 42, "hello");
+        tracing::warn!("goodbye {}", "world");
+        tracing::error!(answer = 42);
+    });
+    assert_eq!(s, "INFO hello\nWARN goodbye world\nERROR\n");
+}
+
+#[cfg(test)]
+#[test]
+fn no_level() {
+    for formatter in [
+        crate::Formatter::Full,
+        crate::Formatter::Compact,
+        crate::Formatter::Pretty,
+        crate::Formatter::Json(None),
+        crate::Formatter::Minimal,
+    ] {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                level: Some(false),
+                formatter: Some(formatter.clone()),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        let s = crate::capture(subscriber, || tracing::error!("hello"));
+        assert!(
+            s.contains("hello") && !s.contains("ERROR"),
+            "{formatter:?}: {s}"
+        );
+    }
+}
+
+#[cfg(all(test, feature = "serde_json1"))]
+#[test]
+fn colorize() {
+    let dir = tempfile::tempdir().unwrap();
+    let run = |ansi, colorize, writer| {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                ansi: Some(ansi),
+                formatter: Some(crate::Formatter::Json(Some(crate::Json {
+                    colorize: Some(colorize),
+                    ..Default::default()
+                }))),
+                ..crate::plain()
+            }),
+            writer: Some(writer),
+            ..Default::default()
+        };
+        crate::capture(subscriber, || tracing::info!(answer = 42, "hello"))
+    };
+    let s = run(true, true, crate::Writer::Stdout);
+    assert!(s.contains("\x1b[32m\"hello\"\x1b[0m"), "{s}");
+    assert!(s.contains("\x1b[36m42\x1b[0m"), "{s}");
+    let stripped = s
+        .split('\x1b')
+        .enumerate()
+        .map(|(ix, it)| match ix {
+            0 => it,
+            _ => it.split_once('m').unwrap().1,
+        })
+        .collect::<String>();
+    serde_json::from_str::<serde_json::Value>(&stripped).unwrap();
+
+    for s in [
+        run(true, false, crate::Writer::Stdout),
+        run(false, true, crate::Writer::Stdout),
+        run(
+            true,
+            true,
+            crate::Writer::File(crate::File {
+                path: dir.path().join("log").into(),
+                ..Default::default()
+            }),
+        ),
+    ] {
+        assert!(!s.contains('\x1b'), "{s}");
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn error_cooldown_on_drop() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (builder, guard) = crate::Subscriber {
+        format: Some(crate::Format {
+            error_cooldown: Some(Duration::from_secs(60)),
+            ..crate::plain()
+        }),
+        writer: Some(crate::Writer::File(crate::File {
+            path: path.clone().into(),
+            ..Default::default()
+        })),
+        filter: Some(crate::Filter {
+            directives: vec!["error".parse().unwrap()],
+            ..Default::default()
+        }),
+        log_bridge: None,
+        total_byte_budget: None,
+    }
+    .builder();
+    tracing::subscriber::with_default(builder.finish(), || {
+        for _ in 0..3 {
+            tracing::error!("disk full")
+        }
+    });
+    drop(guard);
+    let s = std::fs::read_to_string(path).unwrap();
+    assert_eq!(
+        s.lines().last().unwrap(),
+        r#"suppressed duplicate errors error="disk full" error.target="tracing_configuration::format" repeated=2"#
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn error_cooldown() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            error_cooldown: Some(Duration::from_millis(50)),
+            target: Some(false),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        for _ in 0..10 {
+            tracing::error!("disk full")
+        }
+        tracing::error!("different");
+        std::thread::sleep(Duration::from_millis(60));
+        tracing::info!("later");
+    });
+    assert_eq!(
+        s.lines().collect::<Vec<_>>(),
+        [
+            " ERROR disk full",
+            " ERROR different",
+            " ERROR suppressed duplicate errors error=\"disk full\" error.target=\"tracing_configuration::format\" repeated=9",
+            "  INFO later"
+        ]
+    );
+}
@@ -0,0 +1,220 @@
+//! A JSON value whose objects keep their keys in order,
+//! so events can be edited without reordering them
+//! (which [`serde_json::Value`] only does with its `preserve_order` feature).
+
+use std::fmt;
+
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+    Array(Vec<Value>),
+    Object(Map),
+}
+
+impl Value {
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(it) => Some(it),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<Vec<String>> for Value {
+    fn from(value: Vec<String>) -> Self {
+        Self::Array(value.into_iter().map(Self::String).collect())
+    }
+}
+
+impl From<Map> for Value {
+    fn from(value: Map) -> Self {
+        Self::Object(value)
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Self::Null,
+            serde_json::Value::Bool(it) => Self::Bool(it),
+            serde_json::Value::Number(it) => Self::Number(it),
+            serde_json::Value::String(it) => Self::String(it),
+            serde_json::Value::Array(it) => Self::Array(it.into_iter().map(Self::from).collect()),
+            serde_json::Value::Object(it) => {
+                Self::Object(it.into_iter().map(|(k, v)| (k, Self::from(v))).collect())
+            }
+        }
+    }
+}
+
+/// Compact JSON, like [`serde_json::Value`]'s [`Display`](fmt::Display).
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(it) => write!(f, "{it}"),
+            Self::Number(it) => write!(f, "{it}"),
+            Self::String(it) => write_str(f, it),
+            Self::Array(it) => {
+                f.write_str("[")?;
+                for (ix, it) in it.iter().enumerate() {
+                    if ix != 0 {
+                        f.write_str(",")?
+                    }
+                    write!(f, "{it}")?
+                }
+                f.write_str("]")
+            }
+            Self::Object(it) => {
+                f.write_str("{")?;
+                for (ix, (k, v)) in it.iter().enumerate() {
+                    if ix != 0 {
+                        f.write_str(",")?
+                    }
+                    write_str(f, k)?;
+                    write!(f, ":{v}")?
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Write `s` as a quoted and escaped JSON string.
+pub(crate) fn write_str(f: &mut dyn fmt::Write, s: &str) -> fmt::Result {
+    f.write_str(&serde_json::to_string(s).map_err(|_| fmt::Error)?)
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("any JSON value")
+            }
+            fn visit_unit<E>(self) -> Result<Value, E> {
+                Ok(Value::Null)
+            }
+            fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+                Ok(Value::Bool(v))
+            }
+            fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+                Ok(Value::Number(v.into()))
+            }
+            fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+                Ok(Value::Number(v.into()))
+            }
+            fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+                Ok(serde_json::Number::from_f64(v).map_or(Value::Null, Value::Number))
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+                Ok(Value::String(v.into()))
+            }
+            fn visit_string<E>(self, v: String) -> Result<Value, E> {
+                Ok(Value::String(v))
+            }
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+                let mut it = vec![];
+                while let Some(next) = seq.next_element()? {
+                    it.push(next)
+                }
+                Ok(Value::Array(it))
+            }
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+                let mut it = Map::new();
+                while let Some((k, v)) = map.next_entry()? {
+                    it.insert(k, v);
+                }
+                Ok(Value::Object(it))
+            }
+        }
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// A JSON object, in insertion order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct Map(Vec<(String, Value)>);
+
+impl Map {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match serde_json::from_str(s) {
+            Ok(Value::Object(it)) => Some(it),
+            _ => None,
+        }
+    }
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+    pub(crate) fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.0.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+    pub(crate) fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+    /// Replaces any existing value in place.
+    pub(crate) fn insert(&mut self, key: String, value: Value) {
+        match self.get_mut(&key) {
+            Some(it) => *it = value,
+            None => self.0.push((key, value)),
+        }
+    }
+    pub(crate) fn remove(&mut self, key: &str) -> Option<Value> {
+        let ix = self.0.iter().position(|(k, _)| k == key)?;
+        Some(self.0.remove(ix).1)
+    }
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(&str, &mut Value) -> bool) {
+        self.0.retain_mut(|(k, v)| f(k, v))
+    }
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter().map(|(k, v)| (k, v))
+    }
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut Value)> {
+        self.0.iter_mut().map(|(k, v)| (&*k, v))
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (String, Value);
+    type IntoIter = std::vec::IntoIter<(String, Value)>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Extend<(String, Value)> for Map {
+    fn extend<T: IntoIterator<Item = (String, Value)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v)
+        }
+    }
+}
+
+impl FromIterator<(String, Value)> for Map {
+    fn from_iter<T: IntoIterator<Item = (String, Value)>>(iter: T) -> Self {
+        let mut it = Self::new();
+        it.extend(iter);
+        it
+    }
+}
@@ -1,8 +1,15 @@
 //! Configuration-as-a-struct for [`tracing_subscriber::fmt::Subscriber`], to allow
 //! for serializable, dynamic configuration, at the cost of compile-time specialization.
 
+mod ansi;
+#[cfg(all(feature = "schemars1", feature = "toml0_9"))]
+mod documented;
 pub mod format;
+#[cfg(feature = "serde_json1")]
+mod json;
 pub mod time;
+#[cfg(feature = "notify8")]
+pub mod watch;
 pub mod writer;
 
 #[cfg(feature = "clap4")]
@@ -17,11 +24,11 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "serde1")]
 use serde_with::*;
-use std::{fmt, path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, fmt, path::PathBuf, str::FromStr, time::Duration};
 use tracing_subscriber::{filter::Filtered, fmt::format::FmtSpan, EnvFilter, Layer as _};
 use winnow::{
-    combinator::{alt, preceded},
-    token::rest,
+    combinator::{alt, delimited, preceded, separated, separated_pair},
+    token::{rest, take_till},
     Parser as _,
 };
 
@@ -38,6 +45,26 @@ pub struct Subscriber {
     pub writer: Option<Writer>,
     #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
     pub filter: Option<Filter>,
+    /// Also install a [`tracing_log::LogTracer`] in [`Subscriber::try_init_with_reload`],
+    /// so that records from the [`log`] crate reach this subscriber.
+    ///
+    /// Ignored by [`Subscriber::builder`] and [`Subscriber::layer`],
+    /// which don't install anything globally.
+    /// Incompatible with [`Writer::LogBridge`], which would feed events back to [`log`].
+    ///
+    /// Requires the `log` feature.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub log_bridge: Option<bool>,
+    /// Stop writing events once this many bytes have been written in a minute,
+    /// protecting against runaway logging.
+    ///
+    /// Until the minute is up, events are only counted,
+    /// and then a line with the number suppressed is written.
+    /// That line is also written when the [`Guard`] is dropped.
+    ///
+    /// Lines that aren't events, like the [`Format::shutdown_summary`], are always written.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub total_byte_budget: Option<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
@@ -56,21 +83,277 @@ pub struct Filter {
     )]
     #[cfg_attr(feature = "schemars1", schemars(with = "Vec<String>"))]
     pub directives: Vec<tracing_subscriber::filter::Directive>,
+    /// Enable this level globally if none of the [`directives`](Self::directives) do,
+    /// e.g so that an empty filter enables `info` rather than only `error`.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub default_level: Option<Level>,
+    /// Disable all events and spans whose target starts with one of these,
+    /// overriding any [`directives`](Self::directives) for them.
+    ///
+    /// Directives with a span but no target, like `[request]=trace`,
+    /// still enable muted targets within that span.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub mute_targets: Option<Vec<String>>,
 }
 
 impl From<Filter> for EnvFilter {
     fn from(value: Filter) -> Self {
-        let Filter { regex, directives } = value;
+        let Filter {
+            regex,
+            mut directives,
+            default_level,
+            mute_targets,
+        } = value;
         let mut builder = EnvFilter::builder();
         if let Some(regex) = regex {
             builder = builder.with_regex(regex)
         }
+        if let Some(level) = default_level {
+            // a global directive has no target or span, so is just a level
+            let global = directives
+                .iter()
+                .any(|it| it.to_string().parse::<tracing_core::LevelFilter>().is_ok());
+            if !global {
+                directives
+                    .push(tracing_core::LevelFilter::from(tracing_core::Level::from(level)).into())
+            }
+        }
+        for target in mute_targets.into_iter().flatten() {
+            // directives match target prefixes, and a more specific one would take precedence
+            directives.retain(|it| {
+                let it = it.to_string();
+                let end = it.find(['[', '=']).unwrap_or(it.len());
+                !it[..end].starts_with(&target)
+            });
+            // reported by `Subscriber::validate`
+            if let Some(it) = mute_directive(&target) {
+                directives.push(it)
+            }
+        }
         directives
             .into_iter()
             .fold(builder.parse_lossy(""), EnvFilter::add_directive)
     }
 }
 
+/// See [`Filter::mute_targets`], returning [`None`] if `target` isn't one.
+fn mute_directive(target: &str) -> Option<tracing_subscriber::filter::Directive> {
+    // would be parsed as a span
+    if target.contains(['[', ']']) {
+        return None;
+    }
+    format!("{target}=off").parse().ok()
+}
+
+impl Filter {
+    /// Build just the filtering concern, as a [`reload::Layer`](tracing_subscriber::reload::Layer)
+    /// for a custom subscriber stack, with a handle to change the filter at runtime.
+    pub fn into_reloadable_layer<S>(
+        self,
+    ) -> (
+        tracing_subscriber::reload::Layer<EnvFilter, S>,
+        FilterReloadHandle<S>,
+    ) {
+        let (layer, handle) = tracing_subscriber::reload::Layer::new(EnvFilter::from(self.clone()));
+        (
+            layer,
+            FilterReloadHandle {
+                handle,
+                current: std::sync::Arc::new(std::sync::Mutex::new(self)),
+            },
+        )
+    }
+    /// Read comma-separated directives from the first of `vars` that is set and non-empty,
+    /// like `RUST_LOG`.
+    ///
+    /// Directives that fail to parse are ignored.
+    pub fn from_env_chain(vars: &[&str]) -> Option<Self> {
+        Self::from_var_chain(vars, |it| std::env::var(it).ok())
+    }
+    /// See [`Self::from_env_chain`].
+    fn from_var_chain(vars: &[&str], var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let value = vars
+            .iter()
+            .filter_map(|it| var(it))
+            .find(|it| !it.trim().is_empty())?;
+        Some(Self {
+            regex: None,
+            directives: value
+                .split(',')
+                .filter_map(|it| it.trim().parse().ok())
+                .collect(),
+            default_level: None,
+            mute_targets: None,
+        })
+    }
+    /// Recover the directives of an existing [`EnvFilter`],
+    /// e.g one from [`EnvFilter::from_default_env`], so it can be serialized.
+    ///
+    /// [`EnvFilter`] doesn't expose its directives, so they're parsed from its [`Display`](fmt::Display).
+    /// Whether it uses [`regex`](Self::regex) can't be recovered.
+    pub fn try_from_env_filter(filter: &EnvFilter) -> Result<Self, ConversionError> {
+        let s = filter.to_string();
+        let mut directives = vec![];
+        let mut depth = 0usize;
+        let mut start = 0;
+        for (ix, c) in s.char_indices().chain([(s.len(), ',')]) {
+            match c {
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth = depth.saturating_sub(1),
+                // commas also separate the fields of a span directive
+                ',' if depth == 0 => {
+                    let directive = &s[start..ix];
+                    start = ix + 1;
+                    if directive.is_empty() {
+                        continue;
+                    }
+                    directives.push(directive.parse().map_err(|_| ConversionError {
+                        directive: directive.into(),
+                    })?)
+                }
+                _ => {}
+            }
+        }
+        Ok(Self {
+            regex: None,
+            directives,
+            default_level: None,
+            mute_targets: None,
+        })
+    }
+}
+
+/// An [`EnvFilter`] couldn't be converted, see [`Filter::try_from_env_filter`].
+#[derive(Debug)]
+pub struct ConversionError {
+    directive: String,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "couldn't parse directive `{}`", self.directive)
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Changes a [`Filter`] at runtime, see [`Filter::into_reloadable_layer`].
+///
+/// Clones change the same filter.
+#[derive(Debug)]
+pub struct FilterReloadHandle<S> {
+    handle: tracing_subscriber::reload::Handle<EnvFilter, S>,
+    /// [`EnvFilter`] can't be inspected, so keep what it was built from.
+    current: std::sync::Arc<std::sync::Mutex<Filter>>,
+}
+
+impl<S> Clone for FilterReloadHandle<S> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<S> FilterReloadHandle<S> {
+    /// Replace the current filter.
+    pub fn reload(&self, filter: Filter) -> Result<(), tracing_subscriber::reload::Error> {
+        self.modify(|current| *current = filter)
+    }
+    /// Add a directive to the current filter.
+    pub fn add_directive(
+        &self,
+        directive: tracing_subscriber::filter::Directive,
+    ) -> Result<(), tracing_subscriber::reload::Error> {
+        self.modify(|current| current.directives.push(directive))
+    }
+    /// Remove all directives equal to `directive` from the current filter.
+    pub fn remove_directive(
+        &self,
+        directive: &tracing_subscriber::filter::Directive,
+    ) -> Result<(), tracing_subscriber::reload::Error> {
+        self.modify(|current| current.directives.retain(|it| it != directive))
+    }
+    /// The filter that is currently in effect.
+    pub fn current(&self) -> Filter {
+        self.current
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+    fn modify(&self, f: impl FnOnce(&mut Filter)) -> Result<(), tracing_subscriber::reload::Error> {
+        let mut current = self
+            .current
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut filter = current.clone();
+        f(&mut filter);
+        self.handle.reload(EnvFilter::from(filter.clone()))?;
+        *current = filter;
+        Ok(())
+    }
+}
+
+/// Build a [`Directive`](tracing_subscriber::filter::Directive) enabling `level`
+/// for events in `target` within a span called `span` whose `field` is `value`,
+/// i.e `target[span{field=value}]=level`.
+///
+/// Fails if any of the parts would change the meaning of the directive.
+pub fn span_field_directive(
+    target: &str,
+    span: &str,
+    field: &str,
+    value: &str,
+    level: tracing_core::Level,
+) -> Result<tracing_subscriber::filter::Directive, ParseError> {
+    const RESERVED: &[char] = &['[', ']', '{', '}', ',', '='];
+    for (part, reserved) in [
+        (target, RESERVED),
+        (span, RESERVED),
+        (field, RESERVED),
+        // values may contain `=`
+        (value, &RESERVED[..5]),
+    ] {
+        if part.is_empty() || part.contains(reserved) {
+            return Err(ParseError("invalid part in span field directive"));
+        }
+    }
+    format!(
+        "{target}[{span}{{{field}={value}}}]={}",
+        level.as_str().to_lowercase()
+    )
+    .parse()
+    .map_err(|_| ParseError("invalid span field directive"))
+}
+
+/// Parse [`Format::constant_fields`] from a single string, for e.g a `--log-fields` argument.
+///
+/// Pairs are separated by commas, like `service=api,env=prod`.
+/// Values may be double-quoted to contain commas, like `tags="a,b"`.
+pub fn parse_constant_fields(s: &str) -> Result<BTreeMap<String, String>, ParseError> {
+    separated(
+        0..,
+        separated_pair(
+            take_till(1.., ['=', ',']),
+            '=',
+            alt::<_, _, winnow::error::EmptyError, _>((
+                delimited('"', take_till(0.., '"'), '"'),
+                take_till(0.., ','),
+            )),
+        ),
+        ',',
+    )
+    .map(|pairs: Vec<(&str, &str)>| {
+        pairs
+            .into_iter()
+            .map(|(k, v)| (k.trim().into(), v.into()))
+            .collect()
+    })
+    .parse(s)
+    .map_err(|_| ParseError("Expected comma-separated `key=value` pairs"))
+}
+
 #[derive(Debug)]
 pub struct ParseError(&'static str);
 
@@ -82,6 +365,165 @@ impl fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
+impl Subscriber {
+    const PARSE_ERROR: &str =
+        "Expected `;`-separated `writer=..`, `format=..`, `timer=..`, or `filter=..` pairs";
+    /// Parse a one-line config, see the [`FromStr`] implementation.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        s.parse()
+    }
+}
+
+/// Parse a one-line config like `writer=stderr;format=compact;filter=info,my_crate=debug`,
+/// for e.g an environment variable.
+///
+/// Each `;`-separated pair sets one of
+/// - `writer`, see [`Writer`]'s [`FromStr`] implementation.
+/// - `format`, the [`Format::formatter`].
+/// - `timer`, the [`Format::timer`].
+/// - `filter`, comma-separated [`Filter::directives`].
+impl FromStr for Subscriber {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut this = Self::default();
+        for pair in s.split(';').filter(|it| !it.trim().is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or(ParseError(Self::PARSE_ERROR))?;
+            let value = value.trim();
+            match key.trim() {
+                "writer" => this.writer = Some(value.parse()?),
+                "format" => this.format.get_or_insert_default().formatter = Some(value.parse()?),
+                "timer" => this.format.get_or_insert_default().timer = Some(value.parse()?),
+                "filter" => {
+                    this.filter = Some(Filter {
+                        regex: None,
+                        directives: value
+                            .split(',')
+                            .filter(|it| !it.trim().is_empty())
+                            .map(|it| it.trim().parse())
+                            .collect::<Result<_, _>>()
+                            .map_err(|_| {
+                                ParseError("Expected comma-separated filter directives")
+                            })?,
+                        default_level: None,
+                        mute_targets: None,
+                    })
+                }
+                _ => return Err(ParseError(Self::PARSE_ERROR)),
+            }
+        }
+        Ok(this)
+    }
+}
+
+impl TryFrom<&str> for Subscriber {
+    type Error = ParseError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// A problem with a [`Subscriber`]'s configuration, see [`Subscriber::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidationError {
+    /// Where in the config the problem is, e.g `writer.file.path`.
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// The syntax of a serialized [`Subscriber`].
+#[cfg(feature = "serde1")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigFormat {
+    Json,
+    #[cfg(feature = "toml0_9")]
+    Toml,
+    #[cfg(feature = "serde_yaml0_9")]
+    Yaml,
+}
+
+#[cfg(feature = "serde1")]
+impl ConfigFormat {
+    /// If `strict`, unknown fields are an error.
+    fn deserialize(self, s: &str, strict: bool) -> Result<Subscriber, ValidationError> {
+        fn path_to_error<'de, D: serde::Deserializer<'de>>(
+            d: D,
+            strict: bool,
+        ) -> Result<Subscriber, ValidationError>
+        where
+            D::Error: fmt::Display,
+        {
+            let mut unknown = None;
+            let mut callback = |path: serde_ignored::Path| {
+                // `?` marks e.g an `Option`, which isn't interesting to users
+                let path = path.to_string();
+                let path = path.split('.').filter(|it| *it != "?");
+                unknown.get_or_insert_with(|| path.collect::<Vec<_>>().join("."));
+            };
+            let subscriber = serde_path_to_error::deserialize(serde_ignored::Deserializer::new(
+                d,
+                &mut callback,
+            ))
+            .map_err(|e| ValidationError::new(e.path().to_string(), e.inner().to_string()))?;
+            match unknown {
+                Some(path) if strict => Err(ValidationError::new(path, "unknown field")),
+                _ => Ok(subscriber),
+            }
+        }
+        match self {
+            ConfigFormat::Json => path_to_error(&mut serde_json::Deserializer::from_str(s), strict),
+            #[cfg(feature = "toml0_9")]
+            ConfigFormat::Toml => path_to_error(
+                toml::Deserializer::parse(s)
+                    .map_err(|e| ValidationError::new(".", e.to_string()))?,
+                strict,
+            ),
+            #[cfg(feature = "serde_yaml0_9")]
+            ConfigFormat::Yaml => path_to_error(serde_yaml::Deserializer::from_str(s), strict),
+        }
+    }
+}
+
+fn is_valid_strftime(s: &str) -> bool {
+    !chrono::format::StrftimeItems::new(s).any(|it| it == chrono::format::Item::Error)
+}
+
+/// Parse and [validate](Subscriber::validate) a serialized [`Subscriber`],
+/// without building anything.
+#[cfg(feature = "serde1")]
+pub fn validate_config_str(format: ConfigFormat, s: &str) -> Result<(), Vec<ValidationError>> {
+    format
+        .deserialize(s, false)
+        .map_err(|e| vec![e])?
+        .validate()
+}
+
+/// See [`Subscriber::from_bytes`].
+#[cfg(feature = "serde1")]
+impl TryFrom<&[u8]> for Subscriber {
+    type Error = ValidationError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(value)
+    }
+}
+
 /// A totally dynamically configured [`tracing_subscriber::fmt::SubscriberBuilder`].
 pub type SubscriberBuilder<
     N = format::FormatFields,
@@ -94,8 +536,539 @@ pub type SubscriberBuilder<
 pub type Layer<S, N = format::FormatFields, E = format::FormatEvent, W = writer::MakeWriter> =
     Filtered<tracing_subscriber::fmt::Layer<S, N, E, W>, EnvFilter, S>;
 
+/// The layers returned by [`Subscriber::console_quiet_file_verbose`].
+pub type QuietVerboseLayer<S> = tracing_subscriber::layer::Layered<
+    Layer<S>,
+    Filtered<
+        tracing_subscriber::fmt::Layer<
+            S,
+            format::FormatFields,
+            format::FormatEvent,
+            writer::MakeWriter,
+        >,
+        tracing_subscriber::filter::combinator::And<EnvFilter, tracing_core::LevelFilter, S>,
+        S,
+    >,
+    S,
+>;
+
 impl Subscriber {
-    #[expect(clippy::type_complexity)]
+    /// Deserialize from JSON, rejecting unknown fields (which are usually typos)
+    /// rather than ignoring them.
+    ///
+    /// See [`Self::from_config_str_strict`].
+    #[cfg(feature = "serde1")]
+    pub fn from_json_str_strict(s: &str) -> Result<Self, ValidationError> {
+        Self::from_config_str_strict(ConfigFormat::Json, s)
+    }
+    /// Deserialize, rejecting unknown fields (which are usually typos)
+    /// rather than ignoring them.
+    ///
+    /// The returned error points at the first unknown field.
+    #[cfg(feature = "serde1")]
+    pub fn from_config_str_strict(format: ConfigFormat, s: &str) -> Result<Self, ValidationError> {
+        format.deserialize(s, true)
+    }
+    /// Deserialize a config of unknown [`ConfigFormat`], e.g from the network.
+    ///
+    /// Input starting with `{` is JSON.
+    /// Input starting with `[` isn't, because that's also how TOML tables start,
+    /// and a top-level JSON array is never a valid config anyway.
+    /// Anything else is tried as TOML and then YAML, if those features are enabled,
+    /// and the first error is returned if neither parse.
+    ///
+    /// Errors are reported as a [`ValidationError`],
+    /// like the other constructors, rather than a dedicated error type.
+    #[cfg(feature = "serde1")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ValidationError> {
+        let s = std::str::from_utf8(bytes).map_err(|e| ValidationError::new(".", e.to_string()))?;
+        let mut formats = vec![];
+        if !s.trim_start().starts_with('{') {
+            #[cfg(feature = "toml0_9")]
+            formats.push(ConfigFormat::Toml);
+            #[cfg(feature = "serde_yaml0_9")]
+            formats.push(ConfigFormat::Yaml);
+        }
+        if formats.is_empty() {
+            formats.push(ConfigFormat::Json)
+        }
+        let mut first = None;
+        for format in formats {
+            match format.deserialize(s, false) {
+                Ok(it) => return Ok(it),
+                Err(e) => {
+                    first.get_or_insert(e);
+                }
+            }
+        }
+        Err(first.expect("at least one format was tried"))
+    }
+    /// A reference TOML config with every option, each preceded by its documentation.
+    ///
+    /// Options are set to their defaults, so the config behaves like [`Subscriber::default`].
+    /// Options without a default are commented out, with a placeholder of the right shape.
+    #[cfg(all(feature = "schemars1", feature = "toml0_9"))]
+    pub fn documented_default() -> String {
+        documented::toml()
+    }
+    /// Whether the configured [`Writer`] performs blocking file IO on the logging thread,
+    /// which is a hazard for async runtimes.
+    ///
+    /// See [`File::non_blocking`] and [`Rolling::non_blocking`].
+    pub fn has_blocking_writer(&self) -> bool {
+        match &self.writer {
+            Some(Writer::File(File { non_blocking, .. }))
+            | Some(Writer::FileOrStderr(File { non_blocking, .. }))
+            | Some(Writer::Rolling(Rolling { non_blocking, .. })) => non_blocking.is_none(),
+            Some(
+                Writer::Null
+                | Writer::Stdout
+                | Writer::Stderr
+                | Writer::StdSplit
+                | Writer::LogBridge(_),
+            )
+            | None => false,
+        }
+    }
+    /// Enable [`Format::shutdown_summary`].
+    pub fn with_shutdown_summary(mut self) -> Self {
+        self.format
+            .get_or_insert_with(Format::default)
+            .shutdown_summary = Some(true);
+        self
+    }
+    /// Enable [`Subscriber::log_bridge`].
+    #[cfg(feature = "log0_4")]
+    pub fn with_log_bridge(mut self) -> Self {
+        self.log_bridge = Some(true);
+        self
+    }
+    /// Add the given build metadata to [`Format::constant_fields`],
+    /// so that every event can be correlated with a build.
+    pub fn with_build_info(mut self, build_info: BuildInfo) -> Self {
+        let BuildInfo {
+            git_sha,
+            build_time,
+        } = build_info;
+        let fields = self
+            .format
+            .get_or_insert_with(Format::default)
+            .constant_fields
+            .get_or_insert_with(BTreeMap::new);
+        for (k, v) in [("git_sha", git_sha), ("build_time", build_time)] {
+            if let Some(v) = v {
+                fields.insert(String::from(k), v);
+            }
+        }
+        self
+    }
+    /// Add `service` and `version` to [`Format::constant_fields`].
+    ///
+    /// [`env!`] must be expanded in the calling crate to see its package,
+    /// so see [`subscriber_with_pkg!`] to pass `CARGO_PKG_NAME` and `CARGO_PKG_VERSION`.
+    pub fn with_cargo_pkg_fields(mut self, name: &str, version: &str) -> Self {
+        let fields = self
+            .format
+            .get_or_insert_with(Format::default)
+            .constant_fields
+            .get_or_insert_with(BTreeMap::new);
+        fields.insert(String::from("service"), String::from(name));
+        fields.insert(String::from("version"), String::from(version));
+        self
+    }
+    /// Give any [`File`] or [`Rolling`] writer without [`NonBlocking`] config the default config,
+    /// so that logging never blocks e.g an async runtime.
+    ///
+    /// See [`Self::has_blocking_writer`].
+    pub fn ensure_non_blocking(mut self) -> Self {
+        if let Some(Writer::File(File { non_blocking, .. }))
+        | Some(Writer::FileOrStderr(File { non_blocking, .. }))
+        | Some(Writer::Rolling(Rolling { non_blocking, .. })) = &mut self.writer
+        {
+            non_blocking.get_or_insert_with(NonBlocking::default);
+        }
+        self
+    }
+    /// Check this config for problems, without e.g opening any files.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let Self {
+            format,
+            writer,
+            filter,
+            log_bridge,
+            total_byte_budget,
+        } = self;
+        let mut errors = vec![];
+        if let Some(Filter {
+            mute_targets: Some(targets),
+            ..
+        }) = filter
+        {
+            for target in targets {
+                if mute_directive(target).is_none() {
+                    errors.push(ValidationError::new(
+                        "filter.mute_targets",
+                        format!("{target:?} is not a valid target"),
+                    ))
+                }
+            }
+        }
+        if let Some(0) = total_byte_budget {
+            errors.push(ValidationError::new(
+                "total_byte_budget",
+                "must be at least 1",
+            ))
+        }
+        match (log_bridge, writer) {
+            #[cfg(not(feature = "log0_4"))]
+            (Some(true), _) => errors.push(ValidationError::new(
+                "log_bridge",
+                "the `log` feature is disabled",
+            )),
+            #[cfg(feature = "log0_4")]
+            (Some(true), Some(Writer::LogBridge(_))) => errors.push(ValidationError::new(
+                "log_bridge",
+                "records from `log` would be fed back to `log` by `writer.log_bridge`, so remove one of them",
+            )),
+            _ => {}
+        }
+        #[cfg(not(feature = "log0_4"))]
+        if let Some(Writer::LogBridge(_)) = writer {
+            errors.push(ValidationError::new(
+                "writer.log_bridge",
+                "the `log` feature is disabled",
+            ))
+        }
+        if let Some(Format {
+            os_thread_id: Some(true),
+            ..
+        }) = format
+        {
+            if format::os_thread_id().is_none() {
+                errors.push(ValidationError::new(
+                    "format.os_thread_id",
+                    "not supported on this platform, or the `libc` feature is disabled",
+                ))
+            }
+        }
+        if let Some(Format {
+            event_id: Some(kind @ (EventIdKind::Uuid | EventIdKind::Ulid)),
+            ..
+        }) = format
+        {
+            if format::EventId::new(*kind).next().is_none() {
+                errors.push(ValidationError::new(
+                    "format.event_id",
+                    format!("the `{kind}` feature is disabled"),
+                ))
+            }
+        }
+        match format {
+            Some(Format {
+                formatter:
+                    Some(Formatter::Json(Some(Json {
+                        colorize: Some(true),
+                        ..
+                    }))),
+                ..
+            }) => {
+                if let Some(Writer::File(_) | Writer::FileOrStderr(_) | Writer::Rolling(_)) = writer
+                {
+                    errors.push(ValidationError::new(
+                        "format.formatter.json.colorize",
+                        "ignored when writing to files",
+                    ))
+                }
+            }
+            Some(Format {
+                ansi: Some(true),
+                formatter: Some(Formatter::Json(_)),
+                ..
+            }) => errors.push(ValidationError::new(
+                "format.ansi",
+                "the json formatter only uses ANSI escapes if `colorize` is set, so set that or remove this",
+            )),
+            _ => {}
+        }
+        if let (
+            Some(
+                format @ Format {
+                    formatter: Some(Formatter::Json(json)),
+                    ..
+                },
+            ),
+            false,
+        ) = (format, cfg!(feature = "serde_json1"))
+        {
+            // these edit the json formatter's output
+            let json = json.clone().unwrap_or_default();
+            for (set, path) in [
+                (
+                    format
+                        .constant_fields
+                        .as_ref()
+                        .is_some_and(|it| !it.is_empty()),
+                    "format.constant_fields",
+                ),
+                (format.field_precedence.is_some(), "format.field_precedence"),
+                (format.os_thread_id == Some(true), "format.os_thread_id"),
+                (format.event_id.is_some(), "format.event_id"),
+                (format.span_id == Some(true), "format.span_id"),
+                (
+                    format.parent_span_name == Some(true),
+                    "format.parent_span_name",
+                ),
+                (format.uptime_field == Some(true), "format.uptime_field"),
+                (format.error_chain == Some(true), "format.error_chain"),
+                (format.max_message_len.is_some(), "format.max_message_len"),
+                (
+                    format.field_case.is_some_and(|it| it != FieldCase::AsIs),
+                    "format.field_case",
+                ),
+                (
+                    json.colorize == Some(true),
+                    "format.formatter.json.colorize",
+                ),
+                (
+                    json.typed_values == Some(true),
+                    "format.formatter.json.typed_values",
+                ),
+                (
+                    json.message_key.is_some(),
+                    "format.formatter.json.message_key",
+                ),
+                (
+                    json.span_records == Some(true),
+                    "format.formatter.json.span_records",
+                ),
+            ] {
+                if set {
+                    errors.push(ValidationError::new(
+                        path,
+                        "requires the `json` feature with the json formatter",
+                    ))
+                }
+            }
+        }
+        if let Some(Format {
+            timer: Some(Timer::Local(Some(it)) | Timer::Utc(Some(it))),
+            ..
+        }) = format
+        {
+            if !is_valid_strftime(it) {
+                errors.push(ValidationError::new(
+                    "format.timer",
+                    format!("invalid format string `{it}`"),
+                ))
+            }
+        }
+        // options that are ignored because of another
+        if let Some(format) = format {
+            if let (Some(Timer::None), Some(precision)) =
+                (&format.timer, &format.timestamp_precision_by_level)
+            {
+                if !precision.is_empty() {
+                    errors.push(ValidationError::new(
+                        "format.timestamp_precision_by_level",
+                        "has no effect without a timer, so remove this or choose a `timer`",
+                    ))
+                }
+            }
+            if let (Some(_), Some(true)) = (&format.level_icons, format.env_logger_compat) {
+                errors.push(ValidationError::new(
+                    "format.level_icons",
+                    "has no effect with `env_logger_compat`, so remove one of them",
+                ))
+            }
+            if let (Some(_), Some(false)) = (format.level_padding, format.level) {
+                errors.push(ValidationError::new(
+                    "format.level_padding",
+                    "has no effect when `level` is disabled, so remove one of them",
+                ))
+            }
+            if let (Some(true), false) = (
+                format.thread_combined,
+                format.thread_ids == Some(true) && format.thread_names == Some(true),
+            ) {
+                errors.push(ValidationError::new(
+                    "format.thread_combined",
+                    "has no effect unless `thread_ids` and `thread_names` are set",
+                ))
+            }
+            if let (Some(true), Some(Formatter::Json(_))) =
+                (format.relative_time, &format.formatter)
+            {
+                errors.push(ValidationError::new(
+                    "format.relative_time",
+                    "has no effect with the json formatter, so remove this",
+                ))
+            }
+            if let (
+                Some(Formatter::Json(Some(Json {
+                    span_records: Some(true),
+                    ..
+                }))),
+                None,
+            ) = (&format.formatter, &format.span_events)
+            {
+                errors.push(ValidationError::new(
+                    "format.formatter.json.span_records",
+                    "has no effect unless `span_events` are enabled",
+                ))
+            }
+            match (&format.message_key, &format.formatter) {
+                (Some(_), Some(Formatter::Json(_))) => errors.push(ValidationError::new(
+                    "format.message_key",
+                    "has no effect with the json formatter, use `formatter.json.message_key` instead",
+                )),
+                (Some(_), Some(Formatter::Minimal)) => errors.push(ValidationError::new(
+                    "format.message_key",
+                    "has no effect with the minimal formatter, so remove this",
+                )),
+                _ => {}
+            }
+            if let Some(Formatter::Pretty) = format.formatter {
+                for (set, path) in [
+                    (
+                        format
+                            .constant_fields
+                            .as_ref()
+                            .is_some_and(|it| !it.is_empty()),
+                        "format.constant_fields",
+                    ),
+                    (format.error_chain == Some(true), "format.error_chain"),
+                    (format.max_message_len.is_some(), "format.max_message_len"),
+                    (
+                        format.fields_before_message == Some(true),
+                        "format.fields_before_message",
+                    ),
+                    (format.os_thread_id == Some(true), "format.os_thread_id"),
+                    (format.span_id == Some(true), "format.span_id"),
+                    (format.event_id.is_some(), "format.event_id"),
+                    (
+                        format.parent_span_name == Some(true),
+                        "format.parent_span_name",
+                    ),
+                    (format.uptime_field == Some(true), "format.uptime_field"),
+                ] {
+                    if set {
+                        errors.push(ValidationError::new(
+                            path,
+                            "has no effect with the pretty formatter, so remove this",
+                        ))
+                    }
+                }
+            }
+            #[cfg(feature = "serde_json1")]
+            if let Some(Formatter::Json(Some(Json {
+                message_key: Some(key),
+                flatten_event: Some(true),
+                ..
+            }))) = &format.formatter
+            {
+                if format::JSON_KEYS.contains(&key.as_str()) {
+                    errors.push(ValidationError::new(
+                        "format.formatter.json.message_key",
+                        format!("would overwrite the `{key}` key of flattened events"),
+                    ))
+                }
+            }
+        }
+        match writer {
+            Some(
+                writer @ (Writer::File(File {
+                    path, mode, atomic, ..
+                })
+                | Writer::FileOrStderr(File {
+                    path, mode, atomic, ..
+                })),
+            ) => {
+                let key = match writer {
+                    Writer::FileOrStderr(_) => "writer.file_or_stderr",
+                    _ => "writer.file",
+                };
+                if path.as_os_str().is_empty() {
+                    errors.push(ValidationError::new(
+                        format!("{key}.path"),
+                        "must not be empty",
+                    ))
+                }
+                if path.0.to_string_lossy().ends_with(std::path::is_separator) {
+                    errors.push(ValidationError::new(
+                        format!("{key}.path"),
+                        "looks like a directory, but must be a file",
+                    ))
+                }
+                if let (Some(true), FileOpenMode::Append) = (atomic, mode) {
+                    errors.push(ValidationError::new(
+                        format!("{key}.atomic"),
+                        "atomic log files can't be appended to",
+                    ))
+                }
+                if let Writer::File(File {
+                    reopen_on_sighup: Some(true),
+                    ..
+                })
+                | Writer::FileOrStderr(File {
+                    reopen_on_sighup: Some(true),
+                    ..
+                }) = writer
+                {
+                    if !cfg!(all(unix, feature = "libc0_2")) {
+                        errors.push(ValidationError::new(
+                            format!("{key}.reopen_on_sighup"),
+                            "not supported on this platform, or the `libc` feature is disabled",
+                        ))
+                    }
+                    if let Some(true) = atomic {
+                        errors.push(ValidationError::new(
+                            format!("{key}.reopen_on_sighup"),
+                            "atomic log files are only renamed into place on exit, so can't be reopened",
+                        ))
+                    }
+                }
+            }
+            Some(Writer::Rolling(Rolling {
+                directory, roll, ..
+            })) => {
+                if directory.as_os_str().is_empty() {
+                    errors.push(ValidationError::new(
+                        "writer.rolling.directory",
+                        "must not be empty",
+                    ))
+                }
+                if directory.extension().is_some() {
+                    errors.push(ValidationError::new(
+                        "writer.rolling.directory",
+                        "looks like a file, but must be a directory",
+                    ))
+                }
+                if let Some(Roll { limit: Some(0), .. }) = roll {
+                    errors.push(ValidationError::new(
+                        "writer.rolling.roll.limit",
+                        "must be at least 1",
+                    ))
+                }
+                if let Some(Roll {
+                    date_format: Some(it),
+                    ..
+                }) = roll
+                {
+                    if !is_valid_strftime(it) || it.contains(std::path::is_separator) {
+                        errors.push(ValidationError::new(
+                            "writer.rolling.roll.date_format",
+                            format!("invalid format string for a filename `{it}`"),
+                        ))
+                    }
+                }
+            }
+            _ => {}
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
     fn into_components(
         self,
         defer: bool,
@@ -106,7 +1079,7 @@ impl Subscriber {
             format::FormatEvent,
             EnvFilter,
             Guard,
-            Option<FmtSpan>,
+            FmtSpan,
         ),
         writer::Error,
     > {
@@ -114,16 +1087,68 @@ impl Subscriber {
             format,
             writer,
             filter,
+            log_bridge: _,
+            total_byte_budget,
         } = self;
         let mut format = format.unwrap_or_default();
         let writer = writer.unwrap_or_default();
-        let (writer, guard) = match defer {
-            true => writer::MakeWriter::try_new(writer)?,
-            false => writer::MakeWriter::new(writer),
+        if let (
+            Writer::File(_) | Writer::FileOrStderr(_) | Writer::Rolling(_),
+            Some(Formatter::Json(Some(json))),
+        ) = (&writer, &mut format.formatter)
+        {
+            json.colorize = None
+        }
+        let (mut writer, mut guard, fallback) = match writer {
+            Writer::FileOrStderr(file) => writer::MakeWriter::file_or_stderr(file),
+            writer => {
+                let (writer, guard) = match defer {
+                    true => writer::MakeWriter::new(writer),
+                    false => writer::MakeWriter::try_new(writer)?,
+                };
+                (writer, guard, None)
+            }
         };
-        let fields = format::FormatFields::from(format.formatter.clone().unwrap_or_default());
-        let span_events = format.span_events.take();
-        let event = format::FormatEvent::from(format);
+        if let Some(bytes) = total_byte_budget {
+            writer = writer.byte_budget(bytes, Duration::from_secs(60));
+        }
+        let collapse_repeats = format.collapse_repeats == Some(true);
+        if collapse_repeats {
+            writer = writer.collapse_repeats();
+        }
+        let fields = format::FormatFields::from(format.clone());
+        let mut event = format::FormatEvent::from(format);
+        let span_events = event.span_events();
+        if let Some(e) = fallback {
+            event.report(e.to_string())
+        }
+        if let Some((cooldown, json)) = event.error_cooldown() {
+            let writer = writer.clone();
+            guard.on_drop(move || {
+                use std::io::Write as _;
+                use tracing_subscriber::fmt::MakeWriter as _;
+                for it in cooldown.pending(json) {
+                    let _ = writeln!(writer.make_writer(), "{it}");
+                }
+            })
+        }
+        if let Some((counts, json)) = event.level_counts() {
+            let writer = writer.clone();
+            guard.on_drop(move || {
+                use std::io::Write as _;
+                use tracing_subscriber::fmt::MakeWriter as _;
+                let _ = writeln!(writer.make_writer(), "{}", counts.summary(json));
+            })
+        }
+        // write any pending summaries, after the lines above
+        if total_byte_budget.is_some() || collapse_repeats {
+            let writer = writer.clone();
+            guard.on_drop(move || {
+                use std::io::Write as _;
+                use tracing_subscriber::fmt::MakeWriter as _;
+                let _ = writer.make_writer().flush();
+            })
+        }
         let filter = EnvFilter::from(filter.unwrap_or_default());
         Ok((writer, fields, event, filter, guard, span_events))
     }
@@ -141,13 +1166,63 @@ impl Subscriber {
             .into_components(true)
             .expect("errors have been deferred");
         let layer = tracing_subscriber::fmt::layer()
-            .with_span_events(span_events.unwrap_or(FmtSpan::NONE))
+            .with_span_events(span_events)
             .fmt_fields(fields)
             .event_format(event)
             .with_writer(writer)
             .with_filter(filter);
         (layer, guard)
     }
+    /// Create a [`Layer`] writing everything that passes [`Self::filter`] to `file`,
+    /// and one writing only the `WARN` and above of that to stderr,
+    /// with a [`Guard`] for both.
+    ///
+    /// The configured [`Writer`] is ignored.
+    /// Only the `file` layer applies [`Self::total_byte_budget`],
+    /// and [`Format`] options that write when the [`Guard`] is dropped or collapse lines,
+    /// like [`Format::shutdown_summary`].
+    /// Errors when opening files are deferred, see [`Self::layer`].
+    pub fn console_quiet_file_verbose<S>(self, file: File) -> (QuietVerboseLayer<S>, Guard)
+    where
+        S: tracing_core::Subscriber + for<'s> tracing_subscriber::registry::LookupSpan<'s>,
+    {
+        self.quiet_and_verbose(Writer::Stderr, Writer::File(file))
+    }
+    fn quiet_and_verbose<S>(self, quiet: Writer, verbose: Writer) -> (QuietVerboseLayer<S>, Guard)
+    where
+        S: tracing_core::Subscriber + for<'s> tracing_subscriber::registry::LookupSpan<'s>,
+    {
+        use tracing_subscriber::filter::FilterExt as _;
+        // only the file layer summarizes, suppresses or collapses
+        let format = self.format.clone().map(|it| Format {
+            shutdown_summary: None,
+            error_cooldown: None,
+            collapse_repeats: None,
+            ..it
+        });
+        let (writer, fields, event, filter, quiet_guard, span_events) = Self {
+            format,
+            writer: Some(quiet),
+            filter: self.filter.clone(),
+            log_bridge: None,
+            total_byte_budget: None,
+        }
+        .into_components(true)
+        .expect("errors have been deferred");
+        let quiet = tracing_subscriber::fmt::layer()
+            .with_span_events(span_events)
+            .fmt_fields(fields)
+            .event_format(event)
+            .with_writer(writer)
+            .with_filter(filter.and(tracing_core::LevelFilter::WARN));
+        let (verbose, mut guard) = Self {
+            writer: Some(verbose),
+            ..self
+        }
+        .layer();
+        guard.on_drop(move || drop(quiet_guard));
+        (quiet.and_then(verbose), guard)
+    }
     /// Create a new [`Layer`], and a [`Guard`] that handles e.g flushing [`NonBlocking`] IO.
     ///
     /// Returns [`Err`] if e.g opening a log file fails.
@@ -160,7 +1235,7 @@ impl Subscriber {
     {
         let (writer, fields, event, filter, guard, span_events) = self.into_components(false)?;
         let layer = tracing_subscriber::fmt::layer()
-            .with_span_events(span_events.unwrap_or(FmtSpan::NONE))
+            .with_span_events(span_events)
             .fmt_fields(fields)
             .event_format(event)
             .with_writer(writer)
@@ -176,7 +1251,7 @@ impl Subscriber {
             .into_components(true)
             .expect("errors have been deferred");
         let builder = tracing_subscriber::fmt()
-            .with_span_events(span_events.unwrap_or(FmtSpan::NONE))
+            .with_span_events(span_events)
             .fmt_fields(fields)
             .event_format(event)
             .with_writer(writer)
@@ -190,13 +1265,196 @@ impl Subscriber {
     pub fn try_builder(self) -> Result<(SubscriberBuilder, Guard), writer::Error> {
         let (writer, fields, event, filter, guard, span_events) = self.into_components(false)?;
         let builder = tracing_subscriber::fmt()
-            .with_span_events(span_events.unwrap_or(FmtSpan::NONE))
+            .with_span_events(span_events)
             .fmt_fields(fields)
             .event_format(event)
             .with_writer(writer)
             .with_env_filter(filter);
         Ok((builder, guard))
     }
+    /// Create a new [`SubscriberBuilder`], and a [`Guard`] that handles e.g flushing [`NonBlocking`] IO.
+    ///
+    /// If e.g opening a log file fails, fall back to writing to [`Writer::Stderr`],
+    /// and log the original error before the first event.
+    pub fn try_builder_or_report(self) -> (SubscriberBuilder, Guard) {
+        let fallback = Self {
+            writer: Some(Writer::Stderr),
+            ..self.clone()
+        };
+        let (writer, fields, event, filter, guard, span_events) = match self.into_components(false)
+        {
+            Ok(it) => it,
+            Err(e) => {
+                let (writer, fields, mut event, filter, guard, span_events) = fallback
+                    .into_components(false)
+                    .expect("stderr can always be written to");
+                event.report(e.to_string());
+                (writer, fields, event, filter, guard, span_events)
+            }
+        };
+        let builder = tracing_subscriber::fmt()
+            .with_span_events(span_events)
+            .fmt_fields(fields)
+            .event_format(event)
+            .with_writer(writer)
+            .with_env_filter(filter);
+        (builder, guard)
+    }
+    /// Install this as the global default subscriber,
+    /// returning a [`Guard`] that handles e.g flushing [`NonBlocking`] IO,
+    /// and a handle to change the filter at runtime.
+    ///
+    /// If [`Self::log_bridge`] is set, the [`log`] max level is taken from the initial filter,
+    /// so reloading to a more verbose filter will not see more verbose [`log`] records.
+    /// If a [`log`] logger is already installed, e.g by `tracing-subscriber`'s `tracing-log` feature,
+    /// it is left in place.
+    pub fn try_init_with_reload(
+        mut self,
+    ) -> Result<(Guard, FilterReloadHandle<tracing_subscriber::Registry>), InitError> {
+        use tracing_subscriber::layer::SubscriberExt as _;
+        let filter = self.filter.take().unwrap_or_default();
+        #[cfg(feature = "log0_4")]
+        let max_level = EnvFilter::from(filter.clone())
+            .max_level_hint()
+            .unwrap_or(tracing_core::LevelFilter::TRACE);
+        #[cfg(feature = "log0_4")]
+        let log_bridge = self.log_bridge == Some(true);
+        let (filter, handle) = filter.into_reloadable_layer();
+        let (writer, fields, event, _, guard, span_events) =
+            self.into_components(false).map_err(InitError::Writer)?;
+        let layer = tracing_subscriber::fmt::layer()
+            .with_span_events(span_events)
+            .fmt_fields(fields)
+            .event_format(event)
+            .with_writer(writer);
+        // not `try_init`, which may install a `LogTracer` of its own
+        tracing_core::dispatcher::set_global_default(tracing_core::Dispatch::new(
+            tracing_subscriber::registry().with(filter).with(layer),
+        ))
+        .map_err(InitError::Init)?;
+        #[cfg(feature = "log0_4")]
+        if log_bridge {
+            use tracing_log::AsLog as _;
+            // fails only if there's already a logger
+            let _ = tracing_log::LogTracer::builder()
+                .with_max_level(max_level.as_log())
+                .init();
+        }
+        Ok((guard, handle))
+    }
+}
+
+#[cfg(feature = "tempfile3")]
+impl Subscriber {
+    /// A [`Rolling`] writer into a fresh temporary directory,
+    /// which is removed when the returned guard is dropped.
+    ///
+    /// This is intended for examples and tests, and requires the `tempfile` feature.
+    pub fn to_temp_dir() -> std::io::Result<(Self, TempDirGuard)> {
+        let dir = tempfile::Builder::new()
+            .prefix("tracing-configuration-")
+            .tempdir()
+            .map(TempDirGuard)?;
+        let this = Self {
+            writer: Some(Writer::Rolling(Rolling {
+                directory: dir.path().to_path_buf().into(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        Ok((this, dir))
+    }
+}
+
+/// A fresh directory from [`Subscriber::to_temp_dir`],
+/// which is removed along with its contents on drop.
+///
+/// Drop the [`Guard`] first, so that nothing is written after the directory is removed.
+#[cfg(feature = "tempfile3")]
+#[derive(Debug)]
+pub struct TempDirGuard(tempfile::TempDir);
+
+#[cfg(feature = "tempfile3")]
+impl TempDirGuard {
+    /// The directory.
+    pub fn path(&self) -> &std::path::Path {
+        self.0.path()
+    }
+}
+
+#[cfg(feature = "notify8")]
+impl Subscriber {
+    /// Watch the config file at `path`, applying changes to its [`filter`](Self::filter)
+    /// through `handle`, until the returned [`Watcher`](watch::Watcher) is dropped.
+    ///
+    /// `self` should be the config currently in effect.
+    /// Other changes can't be applied live, so are logged as requiring a restart.
+    ///
+    /// The syntax is chosen by `path`'s extension, defaulting to JSON.
+    /// Returns an error if the feature for that syntax isn't enabled.
+    pub fn watch_and_reload<S: 'static>(
+        self,
+        path: impl Into<PathBuf>,
+        handle: FilterReloadHandle<S>,
+    ) -> Result<watch::Watcher, notify::Error> {
+        watch::Watcher::new(self, path.into(), handle)
+    }
+}
+
+/// Error returned by [`Subscriber::try_init_with_reload`].
+#[derive(Debug)]
+pub enum InitError {
+    /// Creating the writer failed, e.g when opening a log file.
+    Writer(writer::Error),
+    /// A global default subscriber has already been set.
+    Init(tracing_core::dispatcher::SetGlobalDefaultError),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::Writer(e) => e.fmt(f),
+            InitError::Init(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for InitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InitError::Writer(e) => e.source(),
+            InitError::Init(e) => e.source(),
+        }
+    }
+}
+
+/// Call [`Subscriber::with_cargo_pkg_fields`] with the calling crate's
+/// `CARGO_PKG_NAME` and `CARGO_PKG_VERSION`.
+///
+/// Takes an existing [`Subscriber`], or starts from the default.
+#[macro_export]
+macro_rules! subscriber_with_pkg {
+    () => {
+        $crate::subscriber_with_pkg!($crate::Subscriber::default())
+    };
+    ($subscriber:expr $(,)?) => {
+        $crate::Subscriber::with_cargo_pkg_fields(
+            $subscriber,
+            ::core::env!("CARGO_PKG_NAME"),
+            ::core::env!("CARGO_PKG_VERSION"),
+        )
+    };
+}
+
+/// Build metadata for [`Subscriber::with_build_info`].
+///
+/// The values are supplied by the caller, e.g from [`env!`] or a build script.
+#[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct BuildInfo {
+    /// Added as a `git_sha` field.
+    pub git_sha: Option<String>,
+    /// Added as a `build_time` field.
+    pub build_time: Option<String>,
 }
 
 /// Config for formatters.
@@ -205,12 +1463,17 @@ impl Subscriber {
 #[cfg_attr(feature = "schemars1", derive(JsonSchema))]
 pub struct Format {
     /// See [`tracing_subscriber::fmt::SubscriberBuilder::with_ansi`].
+    ///
+    /// Only used by the `json` formatter if [`Json::colorize`] is set.
     #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
     pub ansi: Option<bool>,
     /// See [`tracing_subscriber::fmt::SubscriberBuilder::with_target`].
     #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
     pub target: Option<bool>,
     /// See [`tracing_subscriber::fmt::SubscriberBuilder::with_level`].
+    ///
+    /// `false` omits the level from every formatter, including the `level` key of `json`,
+    /// e.g for a file that only receives errors.
     #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
     pub level: Option<bool>,
     /// See [`tracing_subscriber::fmt::SubscriberBuilder::with_thread_ids`].
@@ -242,8 +1505,364 @@ pub struct Format {
     )]
     #[cfg_attr(feature = "schemars1", schemars(with = "Option<Vec<FmtSpanItem>>"))]
     pub span_events: Option<FmtSpan>,
+    /// Render the named numeric fields in human-readable units.
+    ///
+    /// Only applies to the `full` and `compact` formatters.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub humanize_fields: Option<BTreeMap<String, Unit>>,
+    /// How to render the busy and idle times of closed spans.
+    ///
+    /// Only applies to the `full` and `compact` formatters,
+    /// when [`span_events`](Self::span_events) includes [`FmtSpan::CLOSE`].
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub span_timing: Option<SpanEvents>,
+    /// Include the OS-level id of the current thread as an `os_tid` field,
+    /// for correlation with e.g `perf`.
+    ///
+    /// Requires the `libc` feature, and is not supported by the `pretty` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub os_thread_id: Option<bool>,
+    /// When the [`Guard`] is dropped, write a final line with the number of events at each level.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub shutdown_summary: Option<bool>,
+    /// Fields added to every event.
+    ///
+    /// Not supported by the `pretty` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub constant_fields: Option<BTreeMap<String, String>>,
+    /// Which fields are kept when an event's fields, the fields of the spans it's in,
+    /// and [`constant_fields`](Self::constant_fields) share a name.
+    ///
+    /// If unset, constant fields lose to event and span fields,
+    /// which are otherwise all rendered.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub field_precedence: Option<FieldPrecedence>,
+    /// Render the full [`source`](std::error::Error::source) chain of error fields,
+    /// as a `caused by:` list in text, or an array in JSON.
+    ///
+    /// Errors must be recorded as such, e.g `error = &e as &dyn std::error::Error`,
+    /// rather than with `%e`, which only records the outermost message.
+    ///
+    /// Not supported by the `pretty` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub error_chain: Option<bool>,
+    /// Lay events out like [`env_logger`](https://docs.rs/env_logger) does,
+    /// e.g `[2024-01-01T00:00:00Z INFO  my_crate] hello`,
+    /// with the same colors for each level.
+    ///
+    /// Span context is omitted.
+    /// Only applies to the `full` and `compact` formatters.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub env_logger_compat: Option<bool>,
+    /// Render the timestamps of events at these levels with a different precision,
+    /// e.g coarse for `info`, but fine for `trace`.
+    ///
+    /// Other levels use the [`timer`](Self::timer) as normal.
+    /// Listed levels are rendered as RFC 3339 (or seconds for [`Timer::Uptime`]),
+    /// ignoring any custom format string.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub timestamp_precision_by_level: Option<BTreeMap<Level, Precision>>,
+    /// Truncate event messages longer than this many characters, marking them with `…`.
+    ///
+    /// Other fields are left alone.
+    /// Not supported by the `pretty` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_message_len: Option<usize>,
+    /// Write an event's fields before its message, like `user=alice logged in`.
+    ///
+    /// Not supported by the `pretty` or `json` formatters.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub fields_before_message: Option<bool>,
+    /// Don't write targets that are just a crate name, like `my_app`,
+    /// while still writing module paths, like `my_app::db`.
+    ///
+    /// Not supported by the `json` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub hide_default_target: Option<bool>,
+    /// After an `ERROR` event, suppress identical ones (with the same target and message)
+    /// for this many seconds.
+    ///
+    /// The number of suppressed events is reported with the first event after the window closes,
+    /// or when the [`Guard`] is dropped.
+    #[cfg_attr(
+        feature = "serde1",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "As::<Option<DurationSecondsWithFrac<f64, formats::Flexible>>>"
+        )
+    )]
+    #[cfg_attr(feature = "schemars1", schemars(with = "Option<f64>"))]
+    pub error_cooldown: Option<Duration>,
+    /// Write an icon for the level, like `🔴` for `ERROR`.
+    ///
+    /// Not supported by the `json` formatter, or with [`env_logger_compat`](Self::env_logger_compat).
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub level_icons: Option<LevelIcons>,
+    /// Include the id of the current span as a `span_id` field,
+    /// for correlating events within the same span.
+    ///
+    /// Not supported by the `pretty` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub span_id: Option<bool>,
+    /// Rewrite the names of fields, like `requestId` to `request_id`.
+    ///
+    /// Each `.`-separated part of a name is rewritten separately.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub field_case: Option<FieldCase>,
+    /// Collapse consecutive identical lines into a `last message repeated N times` line,
+    /// like `syslogd` does.
+    ///
+    /// Lines must match exactly, so this is most useful without a [`timer`](Self::timer).
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub collapse_repeats: Option<bool>,
+    /// Pad the level to at least this many columns, `5` (the width of `TRACE`) by default.
+    ///
+    /// Only supported by the `full` and `compact` formatters.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub level_padding: Option<usize>,
+    /// Include a unique `event_id` field in each event,
+    /// so that consumers can drop duplicates from e.g at-least-once log shipping.
+    ///
+    /// Not supported by the `pretty` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub event_id: Option<EventIdKind>,
+    /// Show the time since the previous event, like `+0.003s`, after the [`timer`](Self::timer).
+    ///
+    /// Concurrent events may see each other's deltas out of order.
+    ///
+    /// Not supported by the `json` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub relative_time: Option<bool>,
+    /// Show the thread as one token like `worker-1(12)`,
+    /// if both [`thread_names`](Self::thread_names) and [`thread_ids`](Self::thread_ids) are set.
+    ///
+    /// Only supported by the `full` and `compact` formatters.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub thread_combined: Option<bool>,
+    /// Include the name of the current span as a `parent_span` field,
+    /// rather than the whole span list, for flat log analysis.
+    ///
+    /// Events outside of any span don't have the field.
+    /// Not supported by the `pretty` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub parent_span_name: Option<bool>,
+    /// Write the event's message as a quoted field under this key, like `msg="hello world"`,
+    /// so that it can be reliably extracted by e.g `grep`.
+    ///
+    /// Takes precedence over the default of [`Logfmt::message_key`], but not an explicit one.
+    /// Not supported by the `json` or `minimal` formatters, see [`Json::message_key`].
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub message_key: Option<String>,
+    /// Include the seconds since the subscriber was built as an `uptime_secs` field,
+    /// regardless of the [`timer`](Self::timer).
+    ///
+    /// Not supported by the `pretty` formatter.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub uptime_field: Option<bool>,
 }
 
+strum_lite::strum! {
+/// A serializable [`tracing_core::Level`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "clap4", derive(ValueEnum))]
+pub enum Level {
+    Trace = "trace",
+    Debug = "debug",
+    Info = "info",
+    Warn = "warn",
+    Error = "error",
+}}
+
+impl From<Level> for tracing_core::Level {
+    fn from(value: Level) -> Self {
+        match value {
+            Level::Trace => Self::TRACE,
+            Level::Debug => Self::DEBUG,
+            Level::Info => Self::INFO,
+            Level::Warn => Self::WARN,
+            Level::Error => Self::ERROR,
+        }
+    }
+}
+
+impl From<tracing_core::Level> for Level {
+    fn from(value: tracing_core::Level) -> Self {
+        match value {
+            tracing_core::Level::TRACE => Self::Trace,
+            tracing_core::Level::DEBUG => Self::Debug,
+            tracing_core::Level::INFO => Self::Info,
+            tracing_core::Level::WARN => Self::Warn,
+            tracing_core::Level::ERROR => Self::Error,
+        }
+    }
+}
+
+strum_lite::strum! {
+/// How many fractional digits of a second to render,
+/// see [`Format::timestamp_precision_by_level`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "clap4", derive(ValueEnum))]
+pub enum Precision {
+    Seconds = "seconds",
+    Milliseconds = "milliseconds",
+    Microseconds = "microseconds",
+    Nanoseconds = "nanoseconds",
+}}
+
+impl Precision {
+    /// A [`Timer::Local`] or [`Timer::Utc`] format string for RFC 3339 with exactly this many fractional digits,
+    /// like `2024-01-01T12:00:00.000+00:00`.
+    ///
+    /// Unlike `%+`, whose fractional digits vary with the timestamp.
+    pub fn rfc3339_format(self) -> &'static str {
+        match self {
+            Precision::Seconds => "%Y-%m-%dT%H:%M:%S%:z",
+            Precision::Milliseconds => "%Y-%m-%dT%H:%M:%S%.3f%:z",
+            Precision::Microseconds => "%Y-%m-%dT%H:%M:%S%.6f%:z",
+            Precision::Nanoseconds => "%Y-%m-%dT%H:%M:%S%.9f%:z",
+        }
+    }
+}
+
+strum_lite::strum! {
+/// Which source of fields wins a collision, see [`Format::field_precedence`].
+///
+/// The winner is listed first, the losing fields are omitted from the output.
+///
+/// The text formatters render a span's fields once, when it is created,
+/// so there a span field only loses to a constant field.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "clap4", derive(ValueEnum))]
+pub enum FieldPrecedence {
+    /// Event, then span, then constant fields.
+    #[default]
+    Event = "event",
+    /// Span, then event, then constant fields.
+    Span = "span",
+    /// Constant, then event, then span fields.
+    Constant = "constant",
+}}
+
+strum_lite::strum! {
+/// How to generate ids, see [`Format::event_id`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "clap4", derive(ValueEnum))]
+pub enum EventIdKind {
+    /// A random UUID, like `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+    ///
+    /// Requires the `uuid` feature.
+    Uuid = "uuid",
+    /// A ULID, which sorts by time, like `01ARZ3NDEKTSV4RRFFQ69G5FAV`.
+    ///
+    /// Requires the `ulid` feature.
+    Ulid = "ulid",
+    /// A number counting up from `0`, which is only unique within this process.
+    #[default]
+    Counter = "counter",
+}}
+
+strum_lite::strum! {
+/// How to rewrite field names, see [`Format::field_case`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "clap4", derive(ValueEnum))]
+#[cfg_attr(feature = "clap4", value(rename_all = "snake_case"))]
+pub enum FieldCase {
+    /// Leave names alone.
+    #[default]
+    AsIs = "as_is",
+    /// Like `request_id`.
+    Snake = "snake",
+    /// Like `requestId`.
+    Camel = "camel",
+    /// Like `request-id`.
+    Kebab = "kebab",
+}}
+
+/// Config for [`Format::level_icons`].
+#[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+pub struct LevelIcons {
+    /// Override the default emoji for some levels.
+    ///
+    /// Icons are padded to the same width, so that lines stay aligned.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub icons: Option<BTreeMap<Level, String>>,
+    /// Write the level's label after the icon, rather than replacing it.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub label: Option<bool>,
+}
+
+/// Config for the timings on [`FmtSpan::CLOSE`] events, see [`Format::span_timing`].
+///
+/// The timings are measured by [`FormatEvent`](format::FormatEvent),
+/// which is also sent the span's other events for that.
+#[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+pub struct SpanEvents {
+    /// Omit the timings of spans that were open (busy and idle) for less than this, in seconds.
+    #[cfg_attr(
+        feature = "serde1",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "As::<Option<DurationSecondsWithFrac<f64, formats::Flexible>>>"
+        )
+    )]
+    #[cfg_attr(feature = "schemars1", schemars(with = "Option<f64>"))]
+    pub min_duration: Option<Duration>,
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub unit: Option<TimingUnit>,
+}
+
+strum_lite::strum! {
+/// How to render the timings in [`SpanEvents`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "clap4", derive(ValueEnum))]
+pub enum TimingUnit {
+    /// Like `1.23ms` or `456µs`, to three significant figures like [`tracing_subscriber`].
+    #[default]
+    Human = "human",
+    /// Like `1230000ns`.
+    Nanoseconds = "nanoseconds",
+    /// Like `1.23ms`.
+    Milliseconds = "milliseconds",
+}}
+
+strum_lite::strum! {
+/// How to render a numeric field in [`Format::humanize_fields`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "clap4", derive(ValueEnum))]
+pub enum Unit {
+    /// A number of bytes, rendered like `1.5 MB`.
+    Bytes = "bytes",
+    /// A number of nanoseconds, rendered like `2.3s`.
+    Duration = "duration",
+}}
+
 #[cfg(feature = "serde1")]
 struct VecFmtSpan;
 #[cfg(feature = "serde1")]
@@ -318,6 +1937,14 @@ pub enum Formatter {
     Pretty,
     /// See [`tracing_subscriber::fmt::format::Json`].
     Json(Option<Json>),
+    /// `key=value` pairs, like `level=info target=app msg="hello" user="alice"`.
+    ///
+    /// Spans are not included.
+    Logfmt(Option<Logfmt>),
+    /// Just the level and message, like `INFO hello`, for size-constrained targets.
+    ///
+    /// Timestamps, targets, spans and other fields are not included.
+    Minimal,
 }
 
 impl FromStr for Formatter {
@@ -328,9 +1955,11 @@ impl FromStr for Formatter {
             "compact" => Self::Compact,
             "pretty" => Self::Pretty,
             "json" => Self::Json(None),
+            "logfmt" => Self::Logfmt(None),
+            "minimal" => Self::Minimal,
             _ => {
                 return Err(ParseError(
-                    "Expected one of `full`, `compact`, `pretty`, or `json`",
+                    "Expected one of `full`, `compact`, `pretty`, `json`, `logfmt`, or `minimal`",
                 ))
             }
         })
@@ -340,7 +1969,14 @@ impl FromStr for Formatter {
 #[cfg(feature = "clap4")]
 impl ValueEnum for Formatter {
     fn value_variants<'a>() -> &'a [Self] {
-        &[Self::Full, Self::Compact, Self::Pretty, Self::Json(None)]
+        &[
+            Self::Full,
+            Self::Compact,
+            Self::Pretty,
+            Self::Json(None),
+            Self::Logfmt(None),
+            Self::Minimal,
+        ]
     }
 
     fn to_possible_value(&self) -> Option<PossibleValue> {
@@ -349,25 +1985,91 @@ impl ValueEnum for Formatter {
             Formatter::Compact => PossibleValue::new("compact"),
             Formatter::Pretty => PossibleValue::new("pretty"),
             Formatter::Json(_) => PossibleValue::new("json"),
+            Formatter::Logfmt(_) => PossibleValue::new("logfmt"),
+            Formatter::Minimal => PossibleValue::new("minimal"),
         })
     }
 }
 
+/// Options for the json formatter.
+///
+/// Options that edit each event after it's written,
+/// like [`colorize`](Self::colorize) or [`Format::constant_fields`],
+/// require the `json` feature.
+#[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+pub struct Json {
+    /// See [`tracing_subscriber::fmt::format::Json::flatten_event`].
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub flatten_event: Option<bool>,
+    /// See [`tracing_subscriber::fmt::format::Json::with_current_span`].
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub current_span: Option<bool>,
+    /// See [`tracing_subscriber::fmt::format::Json::with_span_list`].
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub span_list: Option<bool>,
+    /// How to delimit records.
+    /// Defaults to [`RecordSeparator::Newline`].
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub record_separator: Option<RecordSeparator>,
+    /// Color keys and values like `jq` does, for reading logs in a terminal.
+    ///
+    /// Disabled if [`Format::ansi`] is `false`, and ignored for `file` and `rolling` writers.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub colorize: Option<bool>,
+    /// Write field values recorded with [`Debug`](std::fmt::Debug) or [`Display`](std::fmt::Display)
+    /// (like `count = %5` or those from the `log` crate) as JSON integers or booleans
+    /// if they are written exactly like one, rather than as strings.
+    ///
+    /// Other values, like `1.10` or `05`, are left as strings, so nothing is lost.
+    /// Values recorded as primitives (like `count = 5`) are always written as such.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub typed_values: Option<bool>,
+    /// The key for the event's message, `message` by default.
+    ///
+    /// The message is nested under `fields` with the other fields,
+    /// unless [`flatten_event`](Self::flatten_event) is set,
+    /// in which case it must not be one of the keys like `level` that are already written.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub message_key: Option<String>,
+    /// Write the events enabled by [`Format::span_events`] as span records,
+    /// rather than as events with a message like `new` or `close`.
+    ///
+    /// Records look like
+    /// `{"timestamp":..,"level":"INFO","event":"span_close","name":"request","target":..,"fields":{"id":1},"time.busy":"1.2ms","time.idle":"3µs"}`,
+    /// where `event` is one of `span_open`, `span_enter`, `span_exit` or `span_close`,
+    /// `fields` are those of the span, and only `span_close` has `time.busy` and `time.idle`.
+    /// Any [`span_list`](Self::span_list) only includes the span's parents.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub span_records: Option<bool>,
+}
+
 #[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "schemars1", derive(JsonSchema))]
-pub struct Json {
-    /// See [`tracing_subscriber::fmt::format::Json::flatten_event`].
-    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
-    pub flatten_event: Option<bool>,
-    /// See [`tracing_subscriber::fmt::format::Json::with_current_span`].
-    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
-    pub current_span: Option<bool>,
-    /// See [`tracing_subscriber::fmt::format::Json::with_span_list`].
+pub struct Logfmt {
+    /// The key for the event's message, `msg` by default.
     #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
-    pub span_list: Option<bool>,
+    pub message_key: Option<String>,
 }
 
+strum_lite::strum! {
+/// How to delimit JSON records.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "clap4", derive(ValueEnum))]
+pub enum RecordSeparator {
+    /// Newline-delimited JSON.
+    #[default]
+    Newline = "newline",
+    /// Start each record with an ASCII record separator (`\x1e`),
+    /// as well as ending it with a newline, per [RFC 7464](https://www.rfc-editor.org/rfc/rfc7464).
+    Rs = "rs",
+}}
+
 /// Which timer implementation to use.
 #[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
@@ -377,11 +2079,16 @@ pub enum Timer {
     /// See [`tracing_subscriber::fmt::SubscriberBuilder::without_time`].
     None,
     /// See [`tracing_subscriber::fmt::time::ChronoLocal`].
+    ///
+    /// As well as `%+` for RFC 3339, the shorthands `%+0`, `%+3`, `%+6` and `%+9`
+    /// are RFC 3339 with that many fractional digits, see [`Precision::rfc3339_format`].
     Local(
         #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
         Option<String>,
     ),
     /// See [`tracing_subscriber::fmt::time::ChronoUtc`].
+    ///
+    /// Accepts the same shorthands as [`Timer::Local`].
     Utc(
         #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
         Option<String>,
@@ -451,11 +2158,34 @@ impl ValueEnum for Timer {
 #[cfg_attr(feature = "schemars1", derive(JsonSchema))]
 #[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
 pub struct File {
-    pub path: PathBuf,
+    pub path: LogFilePath,
     pub mode: FileOpenMode,
     /// Wrap the writer in a [`tracing_appender::non_blocking::NonBlocking`].
     #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
     pub non_blocking: Option<NonBlocking>,
+    /// Write to a `.tmp` sibling of [`path`](Self::path),
+    /// which is renamed into place when the [`Guard`] is dropped,
+    /// so that readers never see a partial file.
+    /// The file is closed first, so later writes fail.
+    ///
+    /// Incompatible with [`FileOpenMode::Append`].
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub atomic: Option<bool>,
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub retry: Option<Retry>,
+    /// After the process receives `SIGHUP`, reopen [`path`](Self::path) for appending
+    /// on the next write, so that tools like `logrotate` can rename or truncate it.
+    ///
+    /// If reopening fails, writes continue to the previous file.
+    ///
+    /// This installs a process-wide `SIGHUP` handler,
+    /// which replaces the default disposition of terminating the process.
+    /// Any handler installed before it is still called.
+    ///
+    /// Only supported on unix with the `libc` feature,
+    /// and incompatible with [`atomic`](Self::atomic).
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub reopen_on_sighup: Option<bool>,
 }
 
 #[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
@@ -464,18 +2194,88 @@ pub struct File {
 #[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
 /// Use a [`tracing_appender::rolling::RollingFileAppender`].
 pub struct Rolling {
-    pub directory: PathBuf,
+    pub directory: LogDirPath,
     pub roll: Option<Roll>,
     /// Wrap the writer in a [`tracing_appender::non_blocking::NonBlocking`].
     #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
     pub non_blocking: Option<NonBlocking>,
+    /// Whenever a new log file is started, atomically write its absolute path to this file,
+    /// for e.g tail-following tools.
+    ///
+    /// This is a portable alternative to a `current` symlink.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub current_path_file: Option<PathBuf>,
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub retry: Option<Retry>,
+}
+
+/// Config for [`Writer::LogBridge`].
+#[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+pub struct LogBridge {
+    /// The level of every record, `info` by default.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub level: Option<Level>,
+    /// The target of every record, `tracing` by default.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub target: Option<String>,
+}
+
+macro_rules! path_newtype {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+        #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+        #[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+        #[cfg_attr(feature = "serde1", serde(transparent))]
+        pub struct $name(pub PathBuf);
+
+        impl From<PathBuf> for $name {
+            fn from(value: PathBuf) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for PathBuf {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl std::ops::Deref for $name {
+            type Target = std::path::Path;
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl AsRef<std::path::Path> for $name {
+            fn as_ref(&self) -> &std::path::Path {
+                &self.0
+            }
+        }
+    };
+}
+
+path_newtype! {
+    /// The path of a single log file, like `/var/log/app.log`.
+    LogFilePath
+}
+
+path_newtype! {
+    /// A directory that log files are created in, like `/var/log/app`.
+    LogDirPath
 }
 
 /// Which writer to use.
 #[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "schemars1", derive(JsonSchema))]
-#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "schemars1", schemars(transform = writer_schema))]
+// `remote = "Self"` generates inherent (de)serialize functions for the structured form,
+// see the manual impls below.
+#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase", remote = "Self"))]
 pub enum Writer {
     /// No writer.
     Null,
@@ -484,13 +2284,127 @@ pub enum Writer {
     Stdout,
     /// Use [`io::stderr`](std::io::stderr).
     Stderr,
+    /// Write `error` and `warn` events to [`Stderr`](Self::Stderr),
+    /// and all other events to [`Stdout`](Self::Stdout).
+    #[cfg_attr(feature = "serde1", serde(rename = "std_split"))]
+    StdSplit,
     File(File),
     Rolling(Rolling),
+    /// Use the [`File`] if it can be opened, or else [`Stderr`](Self::Stderr),
+    /// logging why before the first event.
+    #[cfg_attr(feature = "serde1", serde(rename = "file_or_stderr"))]
+    FileOrStderr(File),
+    /// Forward each line to the [`log`] crate's logger,
+    /// for components that consume `log` records.
+    ///
+    /// Requires the `log` feature, without which opening this writer fails.
+    /// Don't use this if `log` records are forwarded to `tracing`, which would loop.
+    #[cfg_attr(feature = "serde1", serde(rename = "log_bridge"))]
+    LogBridge(LogBridge),
 }
 
 impl Writer {
     const PARSE_ERROR: &str =
-        "Expected one of `null`, `stdout`, `stderr`, `file=<file>`, or `rolling=<directory>`";
+        "Expected one of `null`, `stdout`, `stderr`, `std_split`, `file=<file>`, or `rolling=<directory>`";
+    /// Describe the IO this writer will perform, for e.g startup diagnostics.
+    ///
+    /// Nothing is opened.
+    pub fn describe_io(&self) -> WriterIoInfo {
+        match self {
+            Writer::Null
+            | Writer::Stdout
+            | Writer::Stderr
+            | Writer::StdSplit
+            | Writer::LogBridge(_) => WriterIoInfo {
+                opens_files: false,
+                non_blocking: false,
+                paths: vec![],
+            },
+            Writer::File(File {
+                path, non_blocking, ..
+            })
+            | Writer::FileOrStderr(File {
+                path, non_blocking, ..
+            }) => WriterIoInfo {
+                opens_files: true,
+                non_blocking: non_blocking.is_some(),
+                paths: vec![path.to_path_buf()],
+            },
+            Writer::Rolling(Rolling {
+                directory,
+                non_blocking,
+                current_path_file,
+                ..
+            }) => WriterIoInfo {
+                opens_files: true,
+                non_blocking: non_blocking.is_some(),
+                paths: std::iter::once(directory.to_path_buf())
+                    .chain(current_path_file.clone())
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// See [`Writer::describe_io`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WriterIoInfo {
+    /// Whether files or directories will be opened.
+    pub opens_files: bool,
+    /// Whether writes happen on a background thread.
+    pub non_blocking: bool,
+    /// The log file, or the log directory and any [`Rolling::current_path_file`].
+    pub paths: Vec<PathBuf>,
+}
+
+#[cfg(feature = "serde1")]
+impl Serialize for Writer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Writer::serialize(self, serializer)
+    }
+}
+
+/// Accepts either the structured form (e.g `{ "rolling": { "directory": "/var/log" } }`),
+/// or the [`FromStr`] form (e.g `"rolling=/var/log"`).
+///
+/// Formats that aren't [human readable](serde::Deserializer::is_human_readable)
+/// only accept the structured form.
+#[cfg(feature = "serde1")]
+impl<'de> Deserialize<'de> for Writer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(
+            untagged,
+            expecting = "a writer like `stdout`, `file=<file>`, or a writer object"
+        )]
+        enum Untagged {
+            Structured(#[serde(with = "Writer")] Writer),
+            FromStr(#[serde(deserialize_with = "from_str")] Writer),
+        }
+        fn from_str<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Writer, D::Error> {
+            String::deserialize(d)?
+                .parse()
+                .map_err(serde::de::Error::custom)
+        }
+        if !deserializer.is_human_readable() {
+            return Writer::deserialize(deserializer);
+        }
+        match Untagged::deserialize(deserializer)? {
+            Untagged::Structured(it) | Untagged::FromStr(it) => Ok(it),
+        }
+    }
+}
+
+/// Document the [`FromStr`] forms that the structured form doesn't cover.
+#[cfg(feature = "schemars1")]
+fn writer_schema(schema: &mut schemars::Schema) {
+    if let Some(serde_json::Value::Array(it)) = schema.get_mut("oneOf") {
+        it.push(serde_json::json!({
+            "description": "Shorthand for `null`, a [`File`] at a path, or [`Rolling`] in a directory.",
+            "type": "string",
+            "pattern": "^(none|file=.+|rolling=.*)$",
+        }))
+    }
 }
 
 impl FromStr for Writer {
@@ -501,17 +2415,18 @@ impl FromStr for Writer {
             alt(("null", "none")).map(|_| Self::Null),
             "stdout".map(|_| Self::Stdout),
             "stderr".map(|_| Self::Stderr),
+            "std_split".map(|_| Self::StdSplit),
             preceded("file=", rest)
                 .verify(|it| !str::is_empty(it))
                 .map(|it| {
                     Self::File(File {
-                        path: PathBuf::from(it),
+                        path: PathBuf::from(it).into(),
                         ..Default::default()
                     })
                 }),
             preceded("rolling=", rest).map(|it| {
                 Self::Rolling(Rolling {
-                    directory: PathBuf::from(it),
+                    directory: PathBuf::from(it).into(),
                     ..Default::default()
                 })
             }),
@@ -550,6 +2465,7 @@ impl ValueParserFactory for Writer {
                         PossibleValue::new("null"),
                         PossibleValue::new("stdout"),
                         PossibleValue::new("stderr"),
+                        PossibleValue::new("std_split"),
                         PossibleValue::new("file=<file>"),
                         PossibleValue::new("rolling=<directory>"),
                     ]
@@ -595,6 +2511,12 @@ pub struct Roll {
     /// See [`tracing_appender::rolling::Builder::rotation`].
     #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
     pub rotation: Option<Rotation>,
+    /// A [`chrono` format string](chrono::format::strftime) for the date in rolled filenames,
+    /// which are in UTC.
+    ///
+    /// Has no effect with [`Rotation::Never`], which doesn't date files.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub date_format: Option<String>,
 }
 
 strum_lite::strum! {
@@ -635,6 +2557,725 @@ pub struct NonBlocking {
     pub buffer_length: Option<usize>,
     #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
     pub behaviour: Option<BackpressureBehaviour>,
+    /// See [`tracing_appender::non_blocking::NonBlockingBuilder::thread_name`].
+    ///
+    /// Naming the worker thread makes it identifiable in profilers and crash dumps.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub thread_name: Option<String>,
+}
+
+/// How to report lines that a [`NonBlocking`] writer drops because its buffer is full,
+/// e.g to a metrics system.
+///
+/// See [`writer::MakeWriter::on_overflow`].
+#[derive(Clone)]
+pub enum OverflowAction {
+    /// Add the number of dropped lines to this counter.
+    Count(std::sync::Arc<std::sync::atomic::AtomicU64>),
+    /// Call this with the number of dropped lines.
+    Callback(std::sync::Arc<dyn Fn(u64) + Send + Sync>),
+}
+
+impl fmt::Debug for OverflowAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count(it) => f.debug_tuple("Count").field(it).finish(),
+            Self::Callback(_) => f.debug_tuple("Callback").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Retry writes that fail with transient errors,
+/// like [`Interrupted`](std::io::ErrorKind::Interrupted) or a momentarily full disk,
+/// before surfacing the error.
+#[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars1", derive(JsonSchema))]
+pub struct Retry {
+    /// How many times to try each write, including the first.
+    /// Defaults to `3`.
+    #[cfg_attr(feature = "serde1", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_attempts: Option<u32>,
+    /// How long to wait before the first retry, in seconds, doubling for each subsequent one.
+    /// Defaults to 10 milliseconds.
+    #[cfg_attr(
+        feature = "serde1",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "As::<Option<DurationSecondsWithFrac<f64, formats::Flexible>>>"
+        )
+    )]
+    #[cfg_attr(feature = "schemars1", schemars(with = "Option<f64>"))]
+    pub backoff: Option<Duration>,
+}
+
+/// Run `f` with `subscriber` as the default, returning everything it wrote.
+///
+/// The configured [`Writer`] is ignored, and a missing [`Filter`] enables everything.
+#[cfg(test)]
+fn capture(mut subscriber: Subscriber, f: impl FnOnce()) -> String {
+    subscriber.filter.get_or_insert_with(|| Filter {
+        directives: vec!["trace".parse().unwrap()],
+        ..Default::default()
+    });
+    let (builder, _guard) = subscriber.builder();
+    capture_builder(builder, f)
+}
+
+/// Run `f` with `builder` as the default, returning everything it wrote.
+///
+/// The configured writer is ignored.
+#[cfg(test)]
+fn capture_builder(builder: SubscriberBuilder, f: impl FnOnce()) -> String {
+    use std::sync::{Arc, Mutex};
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+    impl std::io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let capture = Capture::default();
+    let subscriber = builder.with_writer({
+        let capture = capture.clone();
+        move || capture.clone()
+    });
+    tracing::subscriber::with_default(subscriber.finish(), f);
+    let buf = capture.0.lock().unwrap();
+    String::from_utf8(buf.clone()).unwrap()
+}
+
+/// A [`Format`] with deterministic output, for tests.
+#[cfg(test)]
+fn plain() -> Format {
+    Format {
+        ansi: Some(false),
+        timer: Some(Timer::None),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn has_blocking_writer() {
+    let blocking = |writer| {
+        Subscriber {
+            writer,
+            ..Default::default()
+        }
+        .has_blocking_writer()
+    };
+    assert!(!blocking(None));
+    assert!(!blocking(Some(Writer::Stderr)));
+    assert!(blocking(Some(Writer::File(File::default()))));
+    assert!(blocking(Some(Writer::Rolling(Rolling::default()))));
+    assert!(!blocking(Some(Writer::Rolling(Rolling {
+        non_blocking: Some(NonBlocking::default()),
+        ..Default::default()
+    }))));
+}
+
+#[cfg(test)]
+#[test]
+fn ensure_non_blocking() {
+    let file = |non_blocking| Subscriber {
+        writer: Some(Writer::File(File {
+            non_blocking,
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    assert_eq!(
+        file(None).ensure_non_blocking(),
+        file(Some(NonBlocking::default()))
+    );
+    let configured = file(Some(NonBlocking {
+        buffer_length: Some(1),
+        behaviour: Some(BackpressureBehaviour::Block),
+        thread_name: None,
+    }));
+    assert_eq!(configured.clone().ensure_non_blocking(), configured);
+    assert!(!file(None).ensure_non_blocking().has_blocking_writer());
+}
+
+#[cfg(test)]
+#[test]
+fn parse_subscriber() {
+    assert_eq!(
+        "writer=stderr;format=compact"
+            .parse::<Subscriber>()
+            .unwrap(),
+        Subscriber {
+            format: Some(Format {
+                formatter: Some(Formatter::Compact),
+                ..Default::default()
+            }),
+            writer: Some(Writer::Stderr),
+            filter: None,
+            log_bridge: None,
+            total_byte_budget: None,
+        }
+    );
+    assert_eq!(
+        Subscriber::try_from("writer=file=/tmp/log; filter=info,my_crate=debug; timer=uptime")
+            .unwrap(),
+        Subscriber {
+            format: Some(Format {
+                timer: Some(Timer::Uptime),
+                ..Default::default()
+            }),
+            writer: Some("file=/tmp/log".parse().unwrap()),
+            filter: Some(Filter {
+                regex: None,
+                directives: vec!["info".parse().unwrap(), "my_crate=debug".parse().unwrap()],
+                default_level: None,
+                mute_targets: None,
+            }),
+            log_bridge: None,
+            total_byte_budget: None,
+        }
+    );
+    assert_eq!(Subscriber::parse("").unwrap(), Subscriber::default());
+    assert!(Subscriber::parse("writer=stderr;colour=always").is_err());
+    assert!(Subscriber::parse("format=nonsense").is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn try_from_env_filter() {
+    let filter = Filter {
+        regex: None,
+        directives: vec![
+            "info".parse().unwrap(),
+            "my_crate=debug".parse().unwrap(),
+            "[request{id=1,method=\"get\"}]=trace".parse().unwrap(),
+        ],
+        default_level: None,
+        mute_targets: None,
+    };
+    let mut round_tripped = Filter::try_from_env_filter(&EnvFilter::from(filter.clone())).unwrap();
+    round_tripped.directives.sort();
+    let mut expected = filter;
+    expected.directives.sort();
+    assert_eq!(round_tripped, expected);
+    assert_eq!(
+        Filter::try_from_env_filter(&EnvFilter::builder().parse_lossy("")).unwrap(),
+        Filter::default()
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn default_level() {
+    let run = |directives: &[&str]| {
+        let subscriber = Subscriber {
+            format: Some(Format {
+                target: Some(false),
+                ..plain()
+            }),
+            filter: Some(Filter {
+                directives: directives.iter().map(|it| it.parse().unwrap()).collect(),
+                default_level: Some(Level::Info),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        capture(subscriber, || {
+            tracing::info!("hello");
+            tracing::debug!("hidden");
+        })
+    };
+    assert_eq!(run(&[]), "  INFO hello\n");
+    assert_eq!(run(&["other=trace"]), "  INFO hello\n");
+    // an explicit global directive takes precedence
+    assert_eq!(run(&["warn"]), "");
+}
+
+#[cfg(test)]
+#[test]
+fn mute_targets() {
+    let subscriber = Subscriber {
+        format: Some(plain()),
+        filter: Some(Filter {
+            directives: vec![
+                "trace".parse().unwrap(),
+                "noisy::inner=trace".parse().unwrap(),
+                "noisy[span]=trace".parse().unwrap(),
+            ],
+            mute_targets: Some(vec![String::from("noisy")]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let s = capture(subscriber, || {
+        tracing::error!(target: "noisy", "muted");
+        tracing::error!(target: "noisy::inner", "muted");
+        tracing::info_span!("span").in_scope(|| tracing::error!(target: "noisy", "muted"));
+        tracing::trace!(target: "quiet", "shown");
+    });
+    assert_eq!(s, " TRACE quiet: shown\n");
+    let errors = Subscriber {
+        filter: Some(Filter {
+            mute_targets: Some(vec![
+                String::from("noisy"),
+                String::from(""),
+                String::from("[span]"),
+                String::from("a b"),
+            ]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .validate()
+    .unwrap_err();
+    assert_eq!(
+        errors.iter().map(|it| &*it.message).collect::<Vec<_>>(),
+        [
+            r#""" is not a valid target"#,
+            r#""[span]" is not a valid target"#,
+            r#""a b" is not a valid target"#,
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn from_env_chain() {
+    let chain = |first: Option<&str>, second: Option<&str>| {
+        Filter::from_var_chain(&["FIRST", "SECOND"], |it| match it {
+            "FIRST" => first.map(String::from),
+            "SECOND" => second.map(String::from),
+            _ => None,
+        })
+    };
+    assert_eq!(chain(None, None), None);
+
+    let second = Some("info,my_crate=trace");
+    assert_eq!(
+        chain(None, second),
+        Some(Filter {
+            regex: None,
+            directives: vec!["info".parse().unwrap(), "my_crate=trace".parse().unwrap()],
+            default_level: None,
+            mute_targets: None,
+        })
+    );
+
+    assert_eq!(chain(Some(" "), second).unwrap().directives.len(), 2);
+    assert_eq!(
+        chain(Some("warn"), second).unwrap().directives,
+        ["warn".parse().unwrap()]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn into_reloadable_layer() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tracing_subscriber::layer::SubscriberExt as _;
+    struct Count(Arc<AtomicUsize>);
+    impl<S: tracing_core::Subscriber> tracing_subscriber::Layer<S> for Count {
+        fn on_event(
+            &self,
+            _: &tracing_core::Event<'_>,
+            _: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    let filter = |directive: &str| Filter {
+        directives: vec![directive.parse().unwrap()],
+        ..Default::default()
+    };
+    let count = Arc::new(AtomicUsize::new(0));
+    let (layer, handle) = filter("warn").into_reloadable_layer();
+    let subscriber = tracing_subscriber::registry()
+        .with(layer)
+        .with(Count(count.clone()));
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info!("filtered");
+        tracing::warn!("counted");
+        handle.reload(filter("info")).unwrap();
+        tracing::info!("counted");
+
+        let directive = "dynamic=debug".parse().unwrap();
+        tracing::debug!(target: "dynamic", "filtered");
+        handle.add_directive(directive).unwrap();
+        tracing::debug!(target: "dynamic", "counted");
+        assert_eq!(handle.current().directives.len(), 2);
+        handle
+            .remove_directive(&"dynamic=debug".parse().unwrap())
+            .unwrap();
+        tracing::debug!(target: "dynamic", "filtered");
+        assert_eq!(handle.current(), filter("info"));
+    });
+    assert_eq!(count.load(Ordering::Relaxed), 3);
+}
+
+#[cfg(test)]
+#[test]
+fn try_builder_or_report() {
+    let dir = tempfile::tempdir().unwrap();
+    let subscriber = Subscriber {
+        format: Some(plain()),
+        writer: Some(Writer::File(File {
+            path: dir.path().join("missing").join("log").into(),
+            ..Default::default()
+        })),
+        filter: Some(Filter {
+            directives: vec!["trace".parse().unwrap()],
+            ..Default::default()
+        }),
+        log_bridge: None,
+        total_byte_budget: None,
+    };
+    assert!(subscriber.clone().try_builder().is_err());
+    let (builder, _guard) = subscriber.try_builder_or_report();
+    let s = capture_builder(builder, || tracing::error!("hello"));
+    let [report, hello] = s.lines().collect::<Vec<_>>()[..] else {
+        panic!("{s}")
+    };
+    assert!(report.contains("couldn't open log file"), "{report}");
+    assert!(hello.ends_with("hello"), "{hello}");
+}
+
+#[cfg(test)]
+#[test]
+fn file_or_stderr() {
+    let dir = tempfile::tempdir().unwrap();
+    let subscriber = |path: PathBuf| Subscriber {
+        format: Some(plain()),
+        writer: Some(Writer::FileOrStderr(File {
+            path: path.into(),
+            ..Default::default()
+        })),
+        filter: Some(Filter {
+            directives: vec!["trace".parse().unwrap()],
+            ..Default::default()
+        }),
+        log_bridge: None,
+        total_byte_budget: None,
+    };
+    let path = dir.path().join("log");
+    let (builder, guard) = subscriber(path.clone()).try_builder().unwrap();
+    tracing::subscriber::with_default(builder.finish(), || tracing::info!("hello"));
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.ends_with("hello\n"), "{written}");
+    assert_eq!(guard.current_file_len(), Some(written.len() as u64));
+
+    let (builder, guard) = subscriber(dir.path().join("missing").join("log"))
+        .try_builder()
+        .unwrap();
+    assert_eq!(guard.current_file_len(), None);
+    let s = capture_builder(builder, || tracing::info!("hello"));
+    let [report, hello] = s.lines().collect::<Vec<_>>()[..] else {
+        panic!("{s}")
+    };
+    assert!(report.contains("using stderr"), "{report}");
+    assert!(hello.ends_with("hello"), "{hello}");
+}
+
+#[cfg(test)]
+#[test]
+fn collapse_repeats() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (builder, guard) = Subscriber {
+        format: Some(Format {
+            target: Some(false),
+            collapse_repeats: Some(true),
+            ..plain()
+        }),
+        writer: Some(Writer::File(File {
+            path: path.clone().into(),
+            ..Default::default()
+        })),
+        filter: Some(Filter {
+            directives: vec!["info".parse().unwrap()],
+            ..Default::default()
+        }),
+        log_bridge: None,
+        total_byte_budget: Some(1024),
+    }
+    .try_builder()
+    .unwrap();
+    tracing::subscriber::with_default(builder.finish(), || {
+        for _ in 0..3 {
+            tracing::info!("same")
+        }
+        tracing::info!("different");
+        tracing::info!("different");
+    });
+    drop(guard);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "  INFO same\nlast message repeated 2 times\n  INFO different\nlast message repeated 1 time\n"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn byte_budget_shutdown_summary() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (builder, guard) = Subscriber {
+        format: Some(Format {
+            target: Some(false),
+            shutdown_summary: Some(true),
+            ..plain()
+        }),
+        writer: Some(Writer::File(File {
+            path: path.clone().into(),
+            ..Default::default()
+        })),
+        filter: Some(Filter {
+            directives: vec!["info".parse().unwrap()],
+            ..Default::default()
+        }),
+        log_bridge: None,
+        total_byte_budget: Some(10),
+    }
+    .try_builder()
+    .unwrap();
+    tracing::subscriber::with_default(builder.finish(), || {
+        for ix in 0..3 {
+            tracing::info!("event {ix}")
+        }
+    });
+    drop(guard);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "  INFO event 0\n\
+         2 events suppressed, the log byte budget was exceeded\n\
+         shutdown summary: TRACE=0 DEBUG=0 INFO=3 WARN=0 ERROR=0\n"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn with_build_info() {
+    let subscriber = Subscriber {
+        format: Some(Format {
+            target: Some(false),
+            ..plain()
+        }),
+        ..Default::default()
+    }
+    .with_build_info(BuildInfo {
+        git_sha: Some(String::from("abc123")),
+        build_time: Some(String::from("2024-01-01")),
+    });
+    let s = capture(subscriber, || {
+        tracing::info!("one");
+        tracing::warn!("two");
+    });
+    for line in s.lines() {
+        assert!(
+            line.ends_with(r#" build_time="2024-01-01" git_sha="abc123""#),
+            "{line}"
+        );
+    }
+    assert_eq!(s.lines().count(), 2);
+}
+
+#[cfg(test)]
+#[test]
+fn subscriber_with_pkg() {
+    let subscriber = subscriber_with_pkg!(Subscriber {
+        format: Some(Format {
+            target: Some(false),
+            ..plain()
+        }),
+        ..Default::default()
+    });
+    let s = capture(subscriber, || tracing::info!("one"));
+    assert!(
+        s.ends_with(&format!(
+            " service=\"tracing-configuration\" version=\"{}\"\n",
+            env!("CARGO_PKG_VERSION")
+        )),
+        "{s}"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn constant_fields() {
+    let parse = |s| {
+        parse_constant_fields(s).map(|it| {
+            it.into_iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        parse("service=api,env=prod").unwrap(),
+        ["env=prod", "service=api"]
+    );
+    assert_eq!(
+        parse(r#"tags="a,b",env=prod"#).unwrap(),
+        ["env=prod", "tags=a,b"]
+    );
+    assert_eq!(parse("").unwrap(), [""; 0]);
+    assert!(parse("service").is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn span_field() {
+    use tracing_core::Level;
+    assert_eq!(
+        span_field_directive("my_crate", "request", "user_id", "42", Level::TRACE).unwrap(),
+        "my_crate[request{user_id=42}]=trace".parse().unwrap()
+    );
+    assert!(span_field_directive("my_crate", "request", "user_id", "4,2", Level::TRACE).is_err());
+}
+
+#[cfg(all(test, feature = "serde1"))]
+#[test]
+fn strict() {
+    let s = r#"{ "format": { "formater": "json" } }"#;
+    assert_eq!(
+        serde_json::from_str::<Subscriber>(s).unwrap(),
+        Subscriber {
+            format: Some(Format::default()),
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        Subscriber::from_json_str_strict(s).unwrap_err(),
+        ValidationError::new("format.formater", "unknown field")
+    );
+}
+
+#[cfg(all(test, feature = "serde1"))]
+#[test]
+fn from_bytes() {
+    let expected = Subscriber {
+        writer: Some(Writer::Stderr),
+        filter: Some(Filter {
+            directives: vec!["info".parse().unwrap()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let json = br#"
+        { "writer": "stderr", "filter": { "directives": ["info"] } }
+    "#;
+    assert_eq!(Subscriber::try_from(&json[..]).unwrap(), expected);
+    #[cfg(feature = "toml0_9")]
+    {
+        let toml = b"writer = \"stderr\"\n[filter]\ndirectives = [\"info\"]\n";
+        assert_eq!(Subscriber::from_bytes(toml).unwrap(), expected);
+        assert_eq!(
+            Subscriber::from_bytes(b"[filter]\ndirectives = 1\n")
+                .unwrap_err()
+                .path,
+            "filter.directives"
+        );
+    }
+    assert!(Subscriber::from_bytes(b"\xff").is_err());
+}
+
+#[cfg(all(test, feature = "serde1"))]
+#[test]
+fn validate() {
+    validate_config_str(
+        ConfigFormat::Json,
+        r#"{ "writer": { "rolling": { "directory": "/var/log", "roll": { "limit": 3 } } } }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        validate_config_str(ConfigFormat::Json, r#"{ "format": { "ansi": "yes" } }"#).unwrap_err()
+            [0]
+        .path,
+        "format.ansi"
+    );
+    assert_eq!(
+        validate_config_str(
+            ConfigFormat::Json,
+            r#"{ "writer": { "rolling": { "directory": "", "roll": { "limit": 0 } } },
+                 "format": { "timer": { "utc": "%Q" } } }"#,
+        )
+        .unwrap_err()
+        .into_iter()
+        .map(|it| it.path)
+        .collect::<Vec<_>>(),
+        [
+            "format.timer",
+            "writer.rolling.directory",
+            "writer.rolling.roll.limit"
+        ]
+    );
+    assert_eq!(
+        validate_config_str(
+            ConfigFormat::Json,
+            r#"{ "writer": { "rolling": { "directory": "/var/log/app.log" } } }"#,
+        )
+        .unwrap_err(),
+        [ValidationError::new(
+            "writer.rolling.directory",
+            "looks like a file, but must be a directory"
+        )]
+    );
+    for (config, path) in [
+        (
+            r#"{ "formatter": { "json": null }, "ansi": true }"#,
+            "format.ansi",
+        ),
+        (
+            r#"{ "timer": "none", "timestamp_precision_by_level": { "trace": "nanoseconds" } }"#,
+            "format.timestamp_precision_by_level",
+        ),
+        (
+            r#"{ "level_icons": {}, "env_logger_compat": true }"#,
+            "format.level_icons",
+        ),
+        (
+            r#"{ "level_padding": 7, "level": false }"#,
+            "format.level_padding",
+        ),
+        (
+            r#"{ "formatter": { "json": null }, "relative_time": true }"#,
+            "format.relative_time",
+        ),
+        (
+            r#"{ "thread_ids": true, "thread_combined": true }"#,
+            "format.thread_combined",
+        ),
+        (
+            r#"{ "formatter": { "json": null }, "message_key": "msg" }"#,
+            "format.message_key",
+        ),
+        (
+            r#"{ "formatter": { "json": { "span_records": true } } }"#,
+            "format.formatter.json.span_records",
+        ),
+        (
+            r#"{ "formatter": { "json": { "flatten_event": true, "message_key": "level" } } }"#,
+            "format.formatter.json.message_key",
+        ),
+        (
+            r#"{ "formatter": "pretty", "parent_span_name": true }"#,
+            "format.parent_span_name",
+        ),
+        (
+            r#"{ "formatter": "pretty", "uptime_field": true }"#,
+            "format.uptime_field",
+        ),
+    ] {
+        let errors =
+            validate_config_str(ConfigFormat::Json, &format!(r#"{{ "format": {config} }}"#))
+                .unwrap_err();
+        assert!(matches!(&errors[..], [it] if it.path == path), "{errors:?}");
+    }
 }
 
 #[cfg(all(test, feature = "schemars1"))]
@@ -643,3 +3284,168 @@ fn schema() {
     let s = serde_json::to_string_pretty(&schemars::schema_for!(Subscriber)).unwrap();
     expect_test::expect_file!["../snapshots/schema.json"].assert_eq(&s);
 }
+
+#[cfg(all(test, feature = "schemars1", feature = "toml0_9"))]
+#[test]
+fn documented_default() {
+    let s = Subscriber::documented_default();
+    assert!(
+        s.contains("[format]\n")
+            && s.contains("\ntarget = true\n")
+            && s.contains("# ansi = false\n"),
+        "{s}"
+    );
+    let parsed = Subscriber::from_config_str_strict(ConfigFormat::Toml, &s).unwrap();
+    assert_eq!(parsed, documented::defaults());
+    parsed.validate().unwrap();
+    // Every placeholder is of the right shape.
+    fn placeholder(line: &str) -> Option<&str> {
+        let (key, _) = line.strip_prefix("# ")?.split_once(" = ")?;
+        (!key.contains(' ')).then(|| &line[2..])
+    }
+    let uncommented = s
+        .lines()
+        .map(|it| placeholder(it).unwrap_or(it))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_ne!(
+        Subscriber::from_config_str_strict(ConfigFormat::Toml, &uncommented).unwrap(),
+        parsed
+    );
+}
+
+#[cfg(all(test, feature = "schemars1"))]
+#[test]
+fn path_descriptions() {
+    let description = |schema: schemars::Schema| {
+        schema
+            .get("description")
+            .and_then(serde_json::Value::as_str)
+            .map(String::from)
+    };
+    let file = description(schemars::schema_for!(LogFilePath)).unwrap();
+    let dir = description(schemars::schema_for!(LogDirPath)).unwrap();
+    assert!(file.contains("log file") && dir.contains("directory"));
+}
+
+#[cfg(all(test, feature = "serde1"))]
+#[test]
+fn deserialize_writer() {
+    let from_str = serde_json::from_str::<Writer>(r#""rolling=/var/log""#).unwrap();
+    let structured =
+        serde_json::from_str::<Writer>(r#"{ "rolling": { "directory": "/var/log" } }"#).unwrap();
+    assert_eq!(from_str, structured);
+    assert_eq!(
+        serde_json::from_str::<Writer>(r#""stderr""#).unwrap(),
+        Writer::Stderr
+    );
+    assert!(serde_json::from_str::<Writer>(r#""rolling""#).is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn describe_io() {
+    assert_eq!(
+        Writer::Stdout.describe_io(),
+        WriterIoInfo {
+            opens_files: false,
+            non_blocking: false,
+            paths: vec![],
+        }
+    );
+    assert_eq!(
+        Writer::File(File {
+            path: PathBuf::from("/var/log/app.log").into(),
+            non_blocking: Some(NonBlocking::default()),
+            ..Default::default()
+        })
+        .describe_io(),
+        WriterIoInfo {
+            opens_files: true,
+            non_blocking: true,
+            paths: vec![PathBuf::from("/var/log/app.log")],
+        }
+    );
+    assert_eq!(
+        Writer::Rolling(Rolling {
+            directory: PathBuf::from("/var/log/app").into(),
+            current_path_file: Some(PathBuf::from("/run/app/current")),
+            ..Default::default()
+        })
+        .describe_io(),
+        WriterIoInfo {
+            opens_files: true,
+            non_blocking: false,
+            paths: vec![
+                PathBuf::from("/var/log/app"),
+                PathBuf::from("/run/app/current")
+            ],
+        }
+    );
+}
+
+#[cfg(all(test, feature = "tempfile3"))]
+#[test]
+fn to_temp_dir() {
+    let (subscriber, dir) = Subscriber::to_temp_dir().unwrap();
+    let path = dir.path().to_path_buf();
+    let (builder, guard) = Subscriber {
+        format: Some(plain()),
+        filter: Some(Filter {
+            default_level: Some(Level::Info),
+            ..Default::default()
+        }),
+        ..subscriber
+    }
+    .try_builder()
+    .unwrap();
+    tracing::subscriber::with_default(builder.finish(), || tracing::info!("hello"));
+    drop(guard);
+    let written = std::fs::read_dir(&path)
+        .unwrap()
+        .map(|it| std::fs::read_to_string(it.unwrap().path()).unwrap())
+        .collect::<String>();
+    assert!(written.ends_with("hello\n"), "{written}");
+    drop(dir);
+    assert!(!path.exists());
+}
+
+#[cfg(test)]
+#[test]
+fn console_quiet_file_verbose() {
+    use tracing_subscriber::layer::SubscriberExt as _;
+    let dir = tempfile::tempdir().unwrap();
+    let file = |name: &str| {
+        Writer::File(File {
+            path: dir.path().join(name).into(),
+            ..Default::default()
+        })
+    };
+    let (layer, guard) = Subscriber {
+        format: Some(Format {
+            target: Some(false),
+            shutdown_summary: Some(true),
+            ..plain()
+        }),
+        filter: Some(Filter {
+            directives: vec!["info".parse().unwrap()],
+            mute_targets: Some(vec![String::from("muted")]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .quiet_and_verbose(file("console"), file("file"));
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+        tracing::debug!("filtered");
+        tracing::info!("verbose");
+        tracing::warn!("quiet");
+        tracing::warn!(target: "muted", "muted");
+    });
+    drop(guard);
+    let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+    assert_eq!(read("console"), "  WARN quiet\n");
+    assert_eq!(
+        read("file"),
+        "  INFO verbose\n  WARN quiet\nshutdown summary: TRACE=0 DEBUG=0 INFO=1 WARN=1 ERROR=0\n"
+    );
+}
@@ -1,22 +1,134 @@
-use std::time::Instant;
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
+use chrono::SecondsFormat;
 use tracing_subscriber::fmt::{
     format::Writer,
     time::{ChronoLocal, ChronoUtc, SystemTime, Uptime},
 };
 
 /// Implementor of [`tracing_subscriber::fmt::time::FormatTime`], constructed [`From`] [`Timer`](crate::Timer).
-pub struct FormatTime(FormatTimeInner);
+pub struct FormatTime {
+    inner: FormatTimeInner,
+    precision_by_level: BTreeMap<crate::Level, crate::Precision>,
+    /// See [`Self::with_relative_time`].
+    previous: Option<Previous>,
+}
+
+/// When the previous event was formatted, see [`crate::Format::relative_time`].
+///
+/// Shared by every [`FormatTime`] that formats events from the same subscriber,
+/// so the delta is the same whichever one formats the next event.
+pub(crate) type Previous = Arc<Mutex<Option<Instant>>>;
 
 impl From<crate::Timer> for FormatTime {
     fn from(value: crate::Timer) -> Self {
-        Self(value.into())
+        Self {
+            inner: value.into(),
+            precision_by_level: BTreeMap::new(),
+            previous: None,
+        }
+    }
+}
+
+impl FormatTime {
+    /// See [`crate::Format::timestamp_precision_by_level`].
+    pub(crate) fn with_precision_by_level(
+        mut self,
+        precision_by_level: BTreeMap<crate::Level, crate::Precision>,
+    ) -> Self {
+        self.precision_by_level = precision_by_level;
+        self
+    }
+    /// See [`crate::Format::relative_time`].
+    pub(crate) fn with_relative_time(mut self, previous: Option<Previous>) -> Self {
+        self.previous = previous;
+        self
     }
+    /// The time since the previous call, or zero for the first.
+    fn delta(previous: &Mutex<Option<Instant>>) -> Duration {
+        let now = Instant::now();
+        let previous = previous
+            .lock()
+            .unwrap_or_else(|it| it.into_inner())
+            .replace(now);
+        previous.map_or(Duration::ZERO, |it| now.saturating_duration_since(it))
+    }
+}
+
+thread_local! {
+    /// The level of the event currently being formatted, see [`with_level`].
+    static LEVEL: Cell<Option<crate::Level>> = const { Cell::new(None) };
+}
+
+/// Make the level of the event being formatted in `f` available to [`FormatTime`].
+pub(crate) fn with_level<T>(level: tracing_core::Level, f: impl FnOnce() -> T) -> T {
+    let prev = LEVEL.replace(Some(level.into()));
+    let res = f();
+    LEVEL.set(prev);
+    res
 }
 
 impl tracing_subscriber::fmt::time::FormatTime for FormatTime {
     fn format_time(&self, w: &mut Writer<'_>) -> std::fmt::Result {
-        self.0.format_time(w)
+        let Some(previous) = &self.previous else {
+            return self.format_absolute(w);
+        };
+        let mut absolute = String::new();
+        self.format_absolute(&mut Writer::new(&mut absolute))?;
+        let delta = Self::delta(previous).as_secs_f64();
+        match absolute.is_empty() {
+            true => write!(w, "+{delta:.3}s"),
+            false => write!(w, "{absolute} +{delta:.3}s"),
+        }
+    }
+}
+
+impl FormatTime {
+    fn format_absolute(&self, w: &mut Writer<'_>) -> std::fmt::Result {
+        use tracing_subscriber::fmt::time::FormatTime as _;
+        let precision = match LEVEL.get() {
+            Some(level) => self.precision_by_level.get(&level),
+            None => None,
+        };
+        let Some(precision) = precision else {
+            return self.inner.format_time(w);
+        };
+        let (seconds_format, digits) = match precision {
+            crate::Precision::Seconds => (SecondsFormat::Secs, 0),
+            crate::Precision::Milliseconds => (SecondsFormat::Millis, 3),
+            crate::Precision::Microseconds => (SecondsFormat::Micros, 6),
+            crate::Precision::Nanoseconds => (SecondsFormat::Nanos, 9),
+        };
+        match &self.inner {
+            FormatTimeInner::None(()) => Ok(()),
+            FormatTimeInner::Local(_) => write!(
+                w,
+                "{}",
+                chrono::Local::now().to_rfc3339_opts(seconds_format, false)
+            ),
+            FormatTimeInner::Utc(_) | FormatTimeInner::System(_) => write!(
+                w,
+                "{}",
+                chrono::Utc::now().to_rfc3339_opts(seconds_format, true)
+            ),
+            FormatTimeInner::Uptime(start) => {
+                let elapsed = start.elapsed();
+                match digits {
+                    0 => write!(w, "{:4}s", elapsed.as_secs()),
+                    _ => write!(
+                        w,
+                        "{:4}.{:0digits$}s",
+                        elapsed.as_secs(),
+                        elapsed.subsec_nanos() / 10u32.pow(9 - digits as u32)
+                    ),
+                }
+            }
+        }
     }
 }
 
@@ -25,7 +137,19 @@ enum FormatTimeInner {
     Local(ChronoLocal),
     Utc(ChronoUtc),
     System(SystemTime),
-    Uptime(Uptime),
+    Uptime(Instant),
+}
+
+/// Expand the RFC 3339 shorthands documented on [`crate::Timer::Local`].
+fn expand(format: String) -> String {
+    let precision = match &*format {
+        "%+0" => crate::Precision::Seconds,
+        "%+3" => crate::Precision::Milliseconds,
+        "%+6" => crate::Precision::Microseconds,
+        "%+9" => crate::Precision::Nanoseconds,
+        _ => return format,
+    };
+    String::from(precision.rfc3339_format())
 }
 
 impl From<crate::Timer> for FormatTimeInner {
@@ -35,15 +159,15 @@ impl From<crate::Timer> for FormatTimeInner {
             crate::Timer::Local(it) => Self::Local(match it {
                 Some(it) if it == "%+" => ChronoLocal::rfc_3339(),
                 None => ChronoLocal::rfc_3339(),
-                Some(it) => ChronoLocal::new(it),
+                Some(it) => ChronoLocal::new(expand(it)),
             }),
             crate::Timer::Utc(it) => Self::Utc(match it {
                 Some(it) if it == "%+" => ChronoUtc::rfc_3339(),
                 None => ChronoUtc::rfc_3339(),
-                Some(it) => ChronoUtc::new(it),
+                Some(it) => ChronoUtc::new(expand(it)),
             }),
             crate::Timer::System => Self::System(SystemTime),
-            crate::Timer::Uptime => Self::Uptime(Uptime::from(Instant::now())),
+            crate::Timer::Uptime => Self::Uptime(Instant::now()),
         }
     }
 }
@@ -55,7 +179,128 @@ impl tracing_subscriber::fmt::time::FormatTime for FormatTimeInner {
             Self::Local(it) => it.format_time(w),
             Self::Utc(it) => it.format_time(w),
             Self::System(it) => it.format_time(w),
-            Self::Uptime(it) => it.format_time(w),
+            Self::Uptime(it) => Uptime::from(*it).format_time(w),
         }
     }
 }
+
+#[cfg(test)]
+#[test]
+fn relative_time() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            relative_time: Some(true),
+            target: Some(false),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        tracing::info!("first");
+        std::thread::sleep(Duration::from_millis(10));
+        tracing::info!("second");
+    });
+    let [first, second] = s.lines().collect::<Vec<_>>()[..] else {
+        panic!("{s}")
+    };
+    assert_eq!(first, "+0.000s  INFO first");
+    let (delta, rest) = second.split_once("s ").unwrap();
+    let delta = delta.strip_prefix('+').unwrap().parse::<f64>().unwrap();
+    assert!((0.01..1.0).contains(&delta), "{second}");
+    assert_eq!(rest, " INFO second");
+}
+
+#[cfg(test)]
+#[test]
+fn relative_time_shared() {
+    for level_icons in [None, Some(crate::LevelIcons::default())] {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                relative_time: Some(true),
+                hide_default_target: Some(true),
+                level_icons: level_icons.clone(),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        let s = crate::capture(subscriber, || {
+            tracing::info!(target: "my_app", "bare");
+            std::thread::sleep(Duration::from_millis(20));
+            tracing::info!(target: "my_app::db", "qualified");
+            tracing::info!(target: "my_app", "bare");
+        });
+        let deltas = s
+            .lines()
+            .map(|line| {
+                let (delta, _) = line.split_once("s ").unwrap();
+                delta.strip_prefix('+').unwrap().parse::<f64>().unwrap()
+            })
+            .collect::<Vec<_>>();
+        let [first, second, third] = deltas[..] else {
+            panic!("{s}")
+        };
+        assert_eq!(first, 0.0, "{s}");
+        assert!((0.015..1.0).contains(&second), "{s}");
+        // since the qualified event, not the previous bare one
+        assert!(third < 0.015, "{s}");
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn rfc3339_shorthand() {
+    for timer in [
+        crate::Timer::Utc(Some(String::from("%+3"))),
+        crate::Timer::Local(Some(String::from(
+            crate::Precision::Milliseconds.rfc3339_format(),
+        ))),
+    ] {
+        let subscriber = crate::Subscriber {
+            format: Some(crate::Format {
+                timer: Some(timer),
+                ..crate::plain()
+            }),
+            ..Default::default()
+        };
+        let s = crate::capture(subscriber, || tracing::info!("hello"));
+        let (timestamp, _) = s.split_once(' ').unwrap();
+        chrono::DateTime::parse_from_rfc3339(timestamp).unwrap();
+        let (_, fraction) = timestamp.split_once('.').unwrap();
+        let digits = fraction.chars().take_while(char::is_ascii_digit).count();
+        assert_eq!(digits, 3, "{timestamp}");
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn precision_by_level() {
+    let subscriber = crate::Subscriber {
+        format: Some(crate::Format {
+            timer: Some(crate::Timer::Utc(None)),
+            timestamp_precision_by_level: Some(BTreeMap::from_iter([
+                (crate::Level::Info, crate::Precision::Seconds),
+                (crate::Level::Trace, crate::Precision::Nanoseconds),
+            ])),
+            ..crate::plain()
+        }),
+        ..Default::default()
+    };
+    let s = crate::capture(subscriber, || {
+        tracing::info!("coarse");
+        tracing::trace!("fine");
+    });
+    let fraction = |line: &str| {
+        let (timestamp, _) = line.split_once(' ').unwrap();
+        let seconds = timestamp
+            .strip_suffix('Z')
+            .unwrap()
+            .rsplit(':')
+            .next()
+            .unwrap();
+        seconds.split_once('.').map_or(0, |(_, it)| it.len())
+    };
+    let [info, trace] = s.lines().collect::<Vec<_>>()[..] else {
+        panic!("{s}")
+    };
+    assert_eq!((fraction(info), fraction(trace)), (0, 9), "{s}");
+}
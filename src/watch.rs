@@ -0,0 +1,172 @@
+//! Reloading config files as they change, see [`Subscriber::watch_and_reload`].
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use notify::{RecursiveMode, Watcher as _};
+use tracing_core::Level;
+
+use crate::{ConfigFormat, FilterReloadHandle, Subscriber};
+
+/// Changes within this long of each other are applied together.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Stops watching when dropped, see [`Subscriber::watch_and_reload`].
+pub struct Watcher {
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl Watcher {
+    pub(crate) fn new<S: 'static>(
+        current: Subscriber,
+        path: PathBuf,
+        handle: FilterReloadHandle<S>,
+    ) -> notify::Result<Self> {
+        let format = match path.extension().and_then(|it| it.to_str()) {
+            #[cfg(feature = "toml0_9")]
+            Some("toml") => ConfigFormat::Toml,
+            #[cfg(not(feature = "toml0_9"))]
+            Some("toml") => {
+                return Err(notify::Error::generic(
+                    "watching a `.toml` file requires the `toml0_9` feature",
+                ))
+            }
+            #[cfg(feature = "serde_yaml0_9")]
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            #[cfg(not(feature = "serde_yaml0_9"))]
+            Some("yaml" | "yml") => {
+                return Err(notify::Error::generic(
+                    "watching a `.yaml` file requires the `serde_yaml0_9` feature",
+                ))
+            }
+            _ => ConfigFormat::Json,
+        };
+        let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        // editors often replace the file rather than writing to it,
+        // so watch the directory
+        let dir = match path.parent() {
+            Some(it) if !it.as_os_str().is_empty() => it,
+            _ => Path::new("."),
+        };
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        let mut current = Subscriber {
+            filter: None,
+            ..current
+        };
+        std::thread::spawn(move || {
+            // the channel closes when the `Watcher` is dropped
+            while let Ok(event) = rx.recv() {
+                let touches = |it: &notify::Event| {
+                    !it.kind.is_access()
+                        && it.paths.iter().any(|it| it.file_name() == path.file_name())
+                };
+                if !event.is_ok_and(|it| touches(&it)) {
+                    continue;
+                }
+                while rx.recv_timeout(DEBOUNCE).is_ok() {}
+                let mut new = match fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|s| format.deserialize(&s, false).map_err(|e| e.to_string()))
+                {
+                    Ok(it) => it,
+                    Err(e) => {
+                        warn("couldn't reload the config file", &e);
+                        continue;
+                    }
+                };
+                if let Err(e) = handle.reload(new.filter.take().unwrap_or_default()) {
+                    warn("couldn't reload the filter", &e)
+                }
+                if new != current {
+                    warn(
+                        "changes to the config file other than the `filter` require a restart",
+                        &path.display(),
+                    );
+                    // only warn once per change
+                    current = new;
+                }
+            }
+        });
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+static WARNING_CALLSITE: tracing_core::callsite::DefaultCallsite =
+    tracing_core::callsite::DefaultCallsite::new(&WARNING);
+static WARNING: tracing_core::Metadata<'static> = tracing_core::metadata! {
+    name: "config watcher",
+    target: env!("CARGO_CRATE_NAME"),
+    level: Level::WARN,
+    fields: &["message", "error"],
+    callsite: &WARNING_CALLSITE,
+    kind: tracing_core::metadata::Kind::EVENT,
+};
+
+/// Log to the global subscriber, which is usually the one being reloaded.
+fn warn(message: &str, error: &dyn fmt::Display) {
+    let fields = WARNING.fields();
+    let field = |name| fields.field(name).expect("declared in `WARNING`");
+    let message = format_args!("{message}");
+    let error = format_args!("{error}");
+    tracing_core::Event::dispatch(
+        &WARNING,
+        &fields.value_set(&[
+            (
+                &field("message"),
+                Some(&message as &dyn tracing_core::field::Value),
+            ),
+            (&field("error"), Some(&error as _)),
+        ]),
+    )
+}
+
+#[cfg(test)]
+#[test]
+fn watch_and_reload() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tracing_subscriber::layer::SubscriberExt as _;
+    struct Count(Arc<AtomicUsize>);
+    impl<S: tracing_core::Subscriber> tracing_subscriber::Layer<S> for Count {
+        fn on_event(
+            &self,
+            _: &tracing_core::Event<'_>,
+            _: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let config = |directive: &str| {
+        format!(r#"{{ "filter": {{ "directives": ["watch_and_reload={directive}"] }} }}"#)
+    };
+    fs::write(&path, config("warn")).unwrap();
+    let subscriber = Subscriber::from_json_str_strict(&config("warn")).unwrap();
+    let (layer, handle) = subscriber.filter.clone().unwrap().into_reloadable_layer();
+    let _watcher = subscriber.watch_and_reload(&path, handle.clone()).unwrap();
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let subscriber = tracing_subscriber::registry()
+        .with(layer)
+        .with(Count(count.clone()));
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info!(target: "watch_and_reload", "filtered");
+        fs::write(&path, config("info")).unwrap();
+        for _ in 0..100 {
+            if handle.current().directives[0].to_string() == "watch_and_reload=info" {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50))
+        }
+        tracing::info!(target: "watch_and_reload", "counted");
+    });
+    assert_eq!(count.load(Ordering::Relaxed), 1);
+}
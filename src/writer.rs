@@ -1,7 +1,18 @@
-use std::{error::Error as _, fmt, fs::File, io, sync::Arc};
+use std::{
+    error::Error as _,
+    fmt,
+    fs::{self, File},
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 use tracing_appender::{
-    non_blocking::{NonBlocking, NonBlockingBuilder, WorkerGuard},
+    non_blocking::{ErrorCounter, NonBlocking, NonBlockingBuilder, WorkerGuard},
     rolling::{RollingFileAppender, RollingWriter},
 };
 
@@ -10,11 +21,164 @@ use tracing_appender::{
 /// See [`WorkerGuard`] for more.
 pub struct Guard {
     _guard: Option<GuardInner>,
+    /// Runs before any [`WorkerGuard`] is dropped, so anything it writes is flushed.
+    on_drop: Option<Box<dyn FnOnce() + Send + Sync>>,
+    file_len: Option<Arc<FileLen>>,
+}
+
+impl Guard {
+    /// Callbacks run in the order they're added.
+    pub(crate) fn on_drop(&mut self, f: impl FnOnce() + Send + Sync + 'static) {
+        let prev = self.on_drop.take();
+        self.on_drop = Some(Box::new(move || {
+            if let Some(prev) = prev {
+                prev()
+            }
+            f()
+        }))
+    }
+    /// The length of the current log file, for [`File`](crate::File) and [`Rolling`](crate::Rolling) writers.
+    ///
+    /// This includes writes still queued by a [`NonBlocking`](crate::NonBlocking) writer.
+    /// For rolling writers, only bytes written by this process are counted.
+    pub fn current_file_len(&self) -> Option<u64> {
+        self.file_len.as_ref().map(|it| it.get())
+    }
+    /// Get a [`FlushBarrier`] for the records queued so far,
+    /// e.g to make sure they're written after draining requests at shutdown.
+    ///
+    /// Unlike dropping this guard, logging can continue.
+    pub fn barrier(&self) -> FlushBarrier {
+        let worker = match &self._guard {
+            Some(GuardInner::NonBlocking { _guard }) => Some(_guard),
+            Some(GuardInner::Atomic { _guard, .. }) => _guard.as_ref(),
+            None => None,
+        };
+        FlushBarrier(worker.map(|it| {
+            let ticket = it.flushed.next.fetch_add(1, Ordering::Relaxed) + 1;
+            let barrier = (it.sender.clone(), it.flushed.clone(), ticket);
+            FlushBarrier::send(&barrier);
+            barrier
+        }))
+    }
+}
+
+/// Waits for [`NonBlocking`](crate::NonBlocking) records to be written, see [`Guard::barrier`].
+///
+/// This is a no-op for other writers, which write records as they're logged.
+pub struct FlushBarrier(Option<(NonBlocking, Arc<Flushed>, u64)>);
+
+impl FlushBarrier {
+    /// Block until every record queued before this barrier was created has been written and flushed.
+    ///
+    /// Returns early if the [`Guard`] is dropped.
+    pub fn wait(self) {
+        let Some(barrier @ (_, flushed, ticket)) = &self.0 else {
+            return;
+        };
+        let mut state = flushed.state.lock().unwrap_or_else(|it| it.into_inner());
+        while !(state.closed || state.seen >= *ticket) {
+            let (next, timeout) = flushed
+                .cond
+                .wait_timeout(state, Duration::from_millis(100))
+                .unwrap_or_else(|it| it.into_inner());
+            state = next;
+            if timeout.timed_out() {
+                // the marker may have been dropped by a lossy writer
+                drop(state);
+                Self::send(barrier);
+                state = flushed.state.lock().unwrap_or_else(|it| it.into_inner());
+            }
+        }
+    }
+    fn send((sender, _, ticket): &(NonBlocking, Arc<Flushed>, u64)) {
+        let mut marker = BARRIER.to_vec();
+        marker.extend(ticket.to_le_bytes());
+        let _ = io::Write::write(&mut sender.clone(), &marker);
+    }
+}
+
+/// Written through the queue to mark a [`FlushBarrier`], followed by its ticket.
+const BARRIER: &[u8] = b"\0tracing-configuration flush barrier\0";
+
+#[derive(Default)]
+struct Flushed {
+    /// The last ticket handed out.
+    next: AtomicU64,
+    state: Mutex<FlushedState>,
+    cond: Condvar,
+}
+
+#[derive(Default)]
+struct FlushedState {
+    /// The last ticket the worker has reached.
+    seen: u64,
+    /// The worker has stopped.
+    closed: bool,
+}
+
+/// A [`WorkerGuard`], and what a [`FlushBarrier`] needs to talk to the worker.
+struct Worker {
+    _guard: WorkerGuard,
+    sender: NonBlocking,
+    flushed: Arc<Flushed>,
+}
+
+/// Runs on the worker thread, intercepting [`BARRIER`]s.
+struct Barriered<T> {
+    inner: T,
+    flushed: Arc<Flushed>,
+}
+
+impl<T: io::Write> io::Write for Barriered<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match buf.strip_prefix(BARRIER).map(<[u8; 8]>::try_from) {
+            Some(Ok(ticket)) => {
+                let _ = self.inner.flush();
+                let mut state = self
+                    .flushed
+                    .state
+                    .lock()
+                    .unwrap_or_else(|it| it.into_inner());
+                state.seen = state.seen.max(u64::from_le_bytes(ticket));
+                self.flushed.cond.notify_all();
+                Ok(buf.len())
+            }
+            _ => self.inner.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T> Drop for Barriered<T> {
+    fn drop(&mut self) {
+        let mut state = self
+            .flushed
+            .state
+            .lock()
+            .unwrap_or_else(|it| it.into_inner());
+        state.closed = true;
+        self.flushed.cond.notify_all();
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(f) = self.on_drop.take() {
+            f()
+        }
+    }
 }
 
 /// Implementor of [`tracing_subscriber::fmt::MakeWriter`],
 /// constructed from [`Writer`](crate::Writer) in [`Self::new`].
-pub struct MakeWriter(MakeWriterInner);
+///
+/// Clones write to the same destination.
+#[derive(Clone)]
+pub struct MakeWriter(Arc<MakeWriterInner>);
 
 /// Implementor of [`io::Write`], used by [`MakeWriter`].
 pub struct Writer<'a>(WriterInner<'a>);
@@ -39,25 +203,182 @@ impl MakeWriter {
     /// Create a new [`MakeWriter`], and a [`Guard`] that handles e.g flushing [`NonBlocking`] IO.
     ///
     /// Errors when opening files or directories are deferred for the subscriber to handle (typically by logging).
-    /// If you wish to handle them yourself, see [`Self::try_new`].
+    /// If you wish to handle them yourself, see [`Self::try_new`],
+    /// or [`Self::file_or_stderr`] for [`Writer::FileOrStderr`](crate::Writer::FileOrStderr).
     pub fn new(writer: crate::Writer) -> (Self, Guard) {
+        if let crate::Writer::FileOrStderr(file) = writer {
+            let (this, guard, _) = Self::file_or_stderr(file);
+            return (this, guard);
+        }
+        let config = writer.clone();
         let (this, _guard) = MakeWriterInner::new(writer, true).expect("errors have been deferred");
-        (Self(this), Guard { _guard })
+        Self::counted(&config, this, _guard)
     }
     /// Create a new [`MakeWriter`].
     ///
     /// Returns [`Err`] if e.g opening a log file fails.
     /// If you wish the subscriber to handle them (typically by logging), see [`Self::new`].
+    ///
+    /// [`Writer::FileOrStderr`](crate::Writer::FileOrStderr) never fails,
+    /// see [`Self::file_or_stderr`] to learn why it fell back.
     pub fn try_new(writer: crate::Writer) -> Result<(Self, Guard), Error> {
-        MakeWriterInner::new(writer, false).map(|(l, r)| (Self(l), Guard { _guard: r }))
+        if let crate::Writer::FileOrStderr(file) = writer {
+            let (this, guard, _) = Self::file_or_stderr(file);
+            return Ok((this, guard));
+        }
+        let config = writer.clone();
+        MakeWriterInner::new(writer, false).map(|(l, r)| Self::counted(&config, l, r))
+    }
+    /// Like [`Self::new`] with [`Writer::FileOrStderr`](crate::Writer::FileOrStderr),
+    /// also returning why the file couldn't be used.
+    pub fn file_or_stderr(file: crate::File) -> (Self, Guard, Option<Error>) {
+        match Self::try_new(crate::Writer::File(file)) {
+            Ok((this, guard)) => (this, guard, None),
+            Err(e) => {
+                let (this, guard) = Self::new(crate::Writer::Stderr);
+                (this, guard, Some(e))
+            }
+        }
+    }
+    /// Create a writer to a new OS pipe, returning the read end,
+    /// e.g to show formatted logs in a GUI.
+    ///
+    /// Writes block once the pipe's buffer is full,
+    /// so keep reading from it, e.g on a dedicated thread.
+    ///
+    /// Requires the `os_pipe` feature.
+    #[cfg(feature = "os_pipe1")]
+    pub fn os_pipe() -> io::Result<(Self, os_pipe::PipeReader)> {
+        let (reader, writer) = os_pipe::pipe()?;
+        Ok((Self(Arc::new(MakeWriterInner::Pipe(writer))), reader))
+    }
+    /// Track the length of any log file, see [`Guard::current_file_len`].
+    fn counted(
+        config: &crate::Writer,
+        inner: MakeWriterInner,
+        _guard: Option<GuardInner>,
+    ) -> (Self, Guard) {
+        let file_len = FileLen::new(config);
+        (
+            Self(Arc::new(match &file_len {
+                Some(it) => MakeWriterInner::Counting(Box::new(inner), it.clone()),
+                None => inner,
+            })),
+            Guard {
+                _guard,
+                on_drop: None,
+                file_len,
+            },
+        )
+    }
+    /// Collapse repeated lines, see [`Format::collapse_repeats`](crate::Format::collapse_repeats).
+    ///
+    /// Any pending summary is written on flush.
+    pub(crate) fn collapse_repeats(self) -> Self {
+        Self(Arc::new(MakeWriterInner::Collapsing(Collapsing {
+            inner: self.0,
+            state: Mutex::default(),
+        })))
+    }
+    /// See [`crate::Subscriber::total_byte_budget`].
+    pub(crate) fn byte_budget(self, bytes: u64, window: Duration) -> Self {
+        Self(Arc::new(MakeWriterInner::Budgeted(Budgeted {
+            inner: self.0,
+            bytes,
+            window,
+            state: Mutex::new((Instant::now(), 0, 0)),
+        })))
+    }
+    /// Report lines dropped by any [`NonBlocking`](crate::NonBlocking) writer
+    /// because its buffer is full, e.g to a metrics system.
+    ///
+    /// Has no effect with [`BackpressureBehaviour::Block`](crate::BackpressureBehaviour::Block).
+    /// For a [`Subscriber`](crate::Subscriber), see [`SubscriberBuilder::map_writer`](tracing_subscriber::fmt::SubscriberBuilder::map_writer).
+    pub fn on_overflow(self, action: crate::OverflowAction) -> Self {
+        let mut dropped = vec![];
+        self.0.error_counters(&mut dropped);
+        Self(Arc::new(MakeWriterInner::Overflow(
+            self.0,
+            Overflow {
+                dropped,
+                reported: AtomicUsize::new(0),
+                action,
+            },
+        )))
+    }
+    /// Create a [`NonBlocking`](crate::NonBlocking) writer that discards its output,
+    /// but records writes and flushes in [`NullStats`].
+    ///
+    /// Each flush sleeps for `flush_latency`,
+    /// which is useful for checking that the [`Guard`] is held until logs are flushed at shutdown.
+    pub fn instrumented_null(flush_latency: Duration) -> (Self, Guard, NullStats) {
+        let stats = NullStats::default();
+        let (it, _guard) = crate::NonBlocking::default().build(InstrumentedNull {
+            stats: stats.clone(),
+            flush_latency,
+        });
+        (
+            Self(Arc::new(MakeWriterInner::NonBlocking(it))),
+            Guard {
+                _guard: Some(GuardInner::NonBlocking { _guard }),
+                on_drop: None,
+                file_len: None,
+            },
+            stats,
+        )
+    }
+}
+
+/// Counts of the IO performed on a [`MakeWriter::instrumented_null`].
+///
+/// Clones share the same counts.
+#[derive(Debug, Clone, Default)]
+pub struct NullStats(Arc<[AtomicU64; 3]>);
+
+impl NullStats {
+    /// The number of calls to [`io::Write::write`].
+    pub fn writes(&self) -> u64 {
+        self.0[0].load(Ordering::Relaxed)
+    }
+    /// The number of calls to [`io::Write::flush`].
+    pub fn flushes(&self) -> u64 {
+        self.0[1].load(Ordering::Relaxed)
     }
+    /// The total number of bytes written.
+    pub fn bytes(&self) -> u64 {
+        self.0[2].load(Ordering::Relaxed)
+    }
+}
+
+struct InstrumentedNull {
+    stats: NullStats,
+    flush_latency: Duration,
 }
+
+impl io::Write for InstrumentedNull {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stats.0[0].fetch_add(1, Ordering::Relaxed);
+        self.stats.0[2].fetch_add(buf.len() as u64, Ordering::Relaxed);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        std::thread::sleep(self.flush_latency);
+        self.stats.0[1].fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
 impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for MakeWriter {
     type Writer = Writer<'a>;
 
     fn make_writer(&'a self) -> Self::Writer {
         Writer(self.0.make_writer())
     }
+
+    fn make_writer_for(&'a self, meta: &tracing_core::Metadata<'_>) -> Self::Writer {
+        Writer(self.0.make_writer_for(meta))
+    }
 }
 
 impl io::Write for Writer<'_> {
@@ -74,21 +395,62 @@ impl crate::NonBlocking {
     fn build<T: io::Write + Send + 'static>(
         &self,
         writer: T,
-    ) -> (tracing_appender::non_blocking::NonBlocking, WorkerGuard) {
+    ) -> (tracing_appender::non_blocking::NonBlocking, Worker) {
         let Self {
             buffer_length,
             behaviour,
+            thread_name,
         } = self;
         let mut builder = NonBlockingBuilder::default();
         if let Some(it) = buffer_length {
             builder = builder.buffered_lines_limit(*it)
         }
+        if let Some(it) = thread_name {
+            builder = builder.thread_name(it)
+        }
         let builder = match behaviour {
             Some(crate::BackpressureBehaviour::Block) => builder.lossy(false),
             Some(crate::BackpressureBehaviour::Drop) => builder.lossy(true),
             None => builder,
         };
-        builder.finish(writer)
+        let flushed = Arc::<Flushed>::default();
+        let (sender, _guard) = builder.finish(Barriered {
+            inner: writer,
+            flushed: flushed.clone(),
+        });
+        (
+            sender.clone(),
+            Worker {
+                _guard,
+                sender,
+                flushed,
+            },
+        )
+    }
+}
+
+/// See [`MakeWriter::on_overflow`].
+struct Overflow {
+    /// Of every [`NonBlocking`] writer.
+    dropped: Vec<ErrorCounter>,
+    reported: AtomicUsize,
+    action: crate::OverflowAction,
+}
+
+impl Overflow {
+    /// Report any drops since the last call.
+    fn check(&self) {
+        let dropped = self.dropped.iter().map(ErrorCounter::dropped_lines).sum();
+        let reported = self.reported.fetch_max(dropped, Ordering::Relaxed);
+        if dropped > reported {
+            let n = (dropped - reported) as u64;
+            match &self.action {
+                crate::OverflowAction::Count(it) => {
+                    it.fetch_add(n, Ordering::Relaxed);
+                }
+                crate::OverflowAction::Callback(f) => f(n),
+            }
+        }
     }
 }
 
@@ -99,20 +461,86 @@ impl MakeWriterInner {
                 path,
                 mode,
                 non_blocking,
+                atomic,
+                retry,
+                reopen_on_sighup,
+            })
+            // usually resolved by `MakeWriter` before this
+            | crate::Writer::FileOrStderr(crate::File {
+                path,
+                mode,
+                non_blocking,
+                atomic,
+                retry,
+                reopen_on_sighup,
             }) => {
-                match match mode {
-                    crate::FileOpenMode::Truncate => File::create(&path),
-                    crate::FileOpenMode::Append => File::options().append(true).open(&path),
+                let tmp = match atomic {
+                    Some(true) => {
+                        let mut it = path.0.clone().into_os_string();
+                        it.push(".tmp");
+                        Some(PathBuf::from(it))
+                    }
+                    _ => None,
+                };
+                match match (mode, &tmp) {
+                    (crate::FileOpenMode::Truncate, None) => File::create(&path),
+                    (crate::FileOpenMode::Truncate, Some(tmp)) => File::create(tmp),
+                    (crate::FileOpenMode::Append, None) => File::options().append(true).open(&path),
+                    (crate::FileOpenMode::Append, Some(_)) => Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "atomic log files can't be appended to",
+                    )),
                 } {
-                    Ok(it) => match non_blocking {
-                        Some(nb) => {
-                            let (nb, _guard) = nb.build(it);
-                            Ok((
-                                Self::NonBlocking(nb),
-                                Some(GuardInner::NonBlocking { _guard }),
-                            ))
+                    Ok(it) if reopen_on_sighup == Some(true) && tmp.is_none() => {
+                        match Reopen::new(path.0.clone(), it) {
+                            Ok(it) => Ok(match non_blocking {
+                                Some(nb) => {
+                                    let (nb, _guard) = nb.build(Retrying::new(it, retry));
+                                    (Self::NonBlocking(nb), Some(GuardInner::NonBlocking { _guard }))
+                                }
+                                None => (Self::Reopen(it).retrying(retry), None),
+                            }),
+                            Err(e) => match defer {
+                                true => Ok((Self::Deferred(Arc::new(e)), None)),
+                                false => Err(Error(e)),
+                            },
                         }
-                        None => Ok((Self::File(it), None)),
+                    }
+                    Ok(it) => match tmp {
+                        Some(from) => {
+                            let file = AtomicFile(Arc::new(Mutex::new(Some(it))));
+                            let _rename = Rename {
+                                file: file.clone(),
+                                from,
+                                to: path.0.clone(),
+                            };
+                            Ok(match non_blocking {
+                                Some(nb) => {
+                                    let (nb, _guard) = nb.build(Retrying::new(file, retry));
+                                    (
+                                        Self::NonBlocking(nb),
+                                        Some(GuardInner::Atomic {
+                                            _guard: Some(_guard),
+                                            _rename,
+                                        }),
+                                    )
+                                }
+                                None => (
+                                    Self::Atomic(file).retrying(retry),
+                                    Some(GuardInner::Atomic {
+                                        _guard: None,
+                                        _rename,
+                                    }),
+                                ),
+                            })
+                        }
+                        None => Ok(match non_blocking {
+                            Some(nb) => {
+                                let (nb, _guard) = nb.build(Retrying::new(it, retry));
+                                (Self::NonBlocking(nb), Some(GuardInner::NonBlocking { _guard }))
+                            }
+                            None => (Self::File(it).retrying(retry), None),
+                        }),
                     },
                     Err(e) => {
                         let e = io_extra::context(
@@ -130,13 +558,59 @@ impl MakeWriterInner {
                 directory,
                 roll: rolling,
                 non_blocking,
+                current_path_file,
+                retry,
             }) => {
                 let crate::Roll {
                     limit,
                     prefix,
                     suffix,
                     rotation,
+                    date_format,
                 } = rolling.unwrap_or_default();
+                if date_format.is_some() || current_path_file.is_some() {
+                    let rotation = rotation.unwrap_or_default();
+                    let res = DateRolling {
+                        directory: directory.0.clone(),
+                        prefix,
+                        suffix,
+                        // match `RollingFileAppender`
+                        date_format: date_format.unwrap_or_else(|| {
+                            String::from(match rotation {
+                                crate::Rotation::Minutely => "%Y-%m-%d-%H-%M",
+                                crate::Rotation::Hourly => "%Y-%m-%d-%H",
+                                crate::Rotation::Daily | crate::Rotation::Never => "%Y-%m-%d",
+                            })
+                        }),
+                        rotation,
+                        limit,
+                        current_path_file,
+                        state: Mutex::new((0, None)),
+                    }
+                    .open();
+                    return match res {
+                        Ok(it) => match non_blocking {
+                            Some(nb) => {
+                                let (nb, _guard) = nb.build(Retrying::new(it, retry));
+                                Ok((Self::NonBlocking(nb), Some(GuardInner::NonBlocking { _guard })))
+                            }
+                            None => Ok((Self::DateRolling(it).retrying(retry), None)),
+                        },
+                        Err(e) => {
+                            let e = io_extra::context(
+                                e,
+                                format!(
+                                    "couldn't start logging in directory {}",
+                                    directory.display()
+                                ),
+                            );
+                            match defer {
+                                true => Ok((Self::Deferred(Arc::new(e)), None)),
+                                false => Err(Error(e)),
+                            }
+                        }
+                    };
+                }
                 let mut builder = RollingFileAppender::builder();
                 if let Some(limit) = limit {
                     builder = builder.max_log_files(limit)
@@ -165,13 +639,10 @@ impl MakeWriterInner {
                 match builder.build(&directory) {
                     Ok(it) => match non_blocking {
                         Some(nb) => {
-                            let (nb, _guard) = nb.build(it);
-                            Ok((
-                                Self::NonBlocking(nb),
-                                Some(GuardInner::NonBlocking { _guard }),
-                            ))
+                            let (nb, _guard) = nb.build(Retrying::new(it, retry));
+                            Ok((Self::NonBlocking(nb), Some(GuardInner::NonBlocking { _guard })))
                         }
-                        None => Ok((Self::Rolling(it), None)),
+                        None => Ok((Self::Rolling(it).retrying(retry), None)),
                     },
                     Err(e) => {
                         let kind = e
@@ -195,13 +666,252 @@ impl MakeWriterInner {
             }
             crate::Writer::Stdout => Ok((Self::Stdout(io::stdout()), None)),
             crate::Writer::Stderr => Ok((Self::Stderr(io::stderr()), None)),
+            crate::Writer::StdSplit => Ok((
+                Self::SplitByLevel(
+                    Box::new(Self::Stdout(io::stdout())),
+                    Box::new(Self::Stderr(io::stderr())),
+                ),
+                None,
+            )),
             crate::Writer::Null => Ok((Self::Null(io::sink()), None)),
+            #[cfg(feature = "log0_4")]
+            crate::Writer::LogBridge(crate::LogBridge { level, target }) => Ok((
+                Self::LogBridge(LogBridge {
+                    level: match level.unwrap_or(crate::Level::Info) {
+                        crate::Level::Trace => log::Level::Trace,
+                        crate::Level::Debug => log::Level::Debug,
+                        crate::Level::Info => log::Level::Info,
+                        crate::Level::Warn => log::Level::Warn,
+                        crate::Level::Error => log::Level::Error,
+                    },
+                    target: target.unwrap_or_else(|| String::from("tracing")),
+                }),
+                None,
+            )),
+            #[cfg(not(feature = "log0_4"))]
+            crate::Writer::LogBridge(_) => {
+                let e = io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "forwarding to `log` requires the `log` feature",
+                );
+                match defer {
+                    true => Ok((Self::Deferred(Arc::new(e)), None)),
+                    false => Err(Error(e)),
+                }
+            }
+        }
+    }
+    fn retrying(self, retry: Option<crate::Retry>) -> Self {
+        match retry {
+            Some(_) => Self::Retrying(Retrying::new(Box::new(self), retry)),
+            None => self,
+        }
+    }
+}
+
+/// Counts the bytes written to the current log file, see [`Guard::current_file_len`].
+struct FileLen {
+    len: AtomicU64,
+    rotation: crate::Rotation,
+    /// See [`period`].
+    period: AtomicI64,
+}
+
+impl FileLen {
+    /// Call after opening the file, which may truncate it.
+    fn new(writer: &crate::Writer) -> Option<Arc<Self>> {
+        let (len, rotation) = match writer {
+            crate::Writer::File(crate::File { path, atomic, .. })
+            | crate::Writer::FileOrStderr(crate::File { path, atomic, .. }) => {
+                let mut path = path.0.clone().into_os_string();
+                if let Some(true) = atomic {
+                    path.push(".tmp")
+                }
+                let len = fs::metadata(path).map(|it| it.len()).unwrap_or_default();
+                (len, crate::Rotation::Never)
+            }
+            crate::Writer::Rolling(crate::Rolling { roll, .. }) => (
+                0,
+                roll.as_ref()
+                    .and_then(|it| it.rotation.clone())
+                    .unwrap_or_default(),
+            ),
+            crate::Writer::Null
+            | crate::Writer::Stdout
+            | crate::Writer::Stderr
+            | crate::Writer::StdSplit
+            | crate::Writer::LogBridge(_) => return None,
+        };
+        Some(Arc::new(Self {
+            len: AtomicU64::new(len),
+            period: AtomicI64::new(period(&rotation)),
+            rotation,
+        }))
+    }
+    fn add(&self, n: usize) {
+        let period = period(&self.rotation);
+        if self.period.swap(period, Ordering::Relaxed) != period {
+            self.len.store(0, Ordering::Relaxed)
+        }
+        self.len.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    fn get(&self) -> u64 {
+        match self.period.load(Ordering::Relaxed) == period(&self.rotation) {
+            true => self.len.load(Ordering::Relaxed),
+            false => 0,
+        }
+    }
+}
+
+/// The start of the current log file's period, in seconds since the epoch.
+fn period(rotation: &crate::Rotation) -> i64 {
+    let now = chrono::Utc::now().timestamp();
+    match rotation {
+        crate::Rotation::Minutely => now - now.rem_euclid(60),
+        crate::Rotation::Hourly => now - now.rem_euclid(60 * 60),
+        crate::Rotation::Daily => now - now.rem_euclid(60 * 60 * 24),
+        crate::Rotation::Never => 0,
+    }
+}
+
+/// Retries transient errors, see [`crate::Retry`].
+struct Retrying<W> {
+    inner: W,
+    /// Including the first attempt.
+    max_attempts: u32,
+    /// Before the first retry, doubling for each subsequent one.
+    backoff: Duration,
+}
+
+impl<W> Retrying<W> {
+    /// If `retry` is [`None`], writes are only attempted once.
+    fn new(inner: W, retry: Option<crate::Retry>) -> Self {
+        let (max_attempts, backoff) = match retry {
+            Some(crate::Retry {
+                max_attempts,
+                backoff,
+            }) => (
+                max_attempts.unwrap_or(3),
+                backoff.unwrap_or(Duration::from_millis(10)),
+            ),
+            None => (1, Duration::ZERO),
+        };
+        Self {
+            inner,
+            max_attempts,
+            backoff,
+        }
+    }
+    fn retry<T>(&mut self, mut f: impl FnMut(&mut W) -> io::Result<T>) -> io::Result<T> {
+        let Self {
+            inner,
+            max_attempts,
+            backoff,
+        } = self;
+        with_retries(*max_attempts, *backoff, || f(inner))
+    }
+}
+
+/// Calls `f` until it succeeds, fails with a non-transient error, or runs out of attempts.
+fn with_retries<T>(
+    max_attempts: u32,
+    mut backoff: Duration,
+    mut f: impl FnMut() -> io::Result<T>,
+) -> io::Result<T> {
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Err(e)
+                if attempt < max_attempts
+                    && matches!(
+                        e.kind(),
+                        io::ErrorKind::Interrupted
+                            | io::ErrorKind::WouldBlock
+                            | io::ErrorKind::TimedOut
+                            | io::ErrorKind::StorageFull
+                    ) =>
+            {
+                std::thread::sleep(backoff);
+                backoff = backoff.saturating_mul(2);
+                attempt += 1;
+            }
+            res => return res,
         }
     }
 }
 
+impl<W: io::Write> io::Write for Retrying<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.retry(|it| it.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.retry(|it| it.flush())
+    }
+}
+
 enum GuardInner {
-    NonBlocking { _guard: WorkerGuard },
+    NonBlocking {
+        _guard: Worker,
+    },
+    /// Fields are dropped in order, so the file is flushed before it's renamed.
+    Atomic {
+        _guard: Option<Worker>,
+        _rename: Rename,
+    },
+}
+
+/// Move an [atomic](crate::File::atomic) log file into place on drop.
+struct Rename {
+    file: AtomicFile,
+    from: PathBuf,
+    to: PathBuf,
+}
+
+impl Drop for Rename {
+    fn drop(&mut self) {
+        // Windows can't rename a file that's still open.
+        drop(
+            self.file
+                .0
+                .lock()
+                .unwrap_or_else(|it| it.into_inner())
+                .take(),
+        );
+        let _ = fs::rename(&self.from, &self.to);
+    }
+}
+
+/// An [atomic](crate::File::atomic) log file, closed by [`Rename`].
+#[derive(Clone)]
+struct AtomicFile(Arc<Mutex<Option<File>>>);
+
+impl io::Write for &AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut *self.0.lock().unwrap_or_else(|it| it.into_inner()) {
+            Some(it) => it.write(buf),
+            None => Err(io::Error::other(
+                "atomic log file was already moved into place",
+            )),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut *self.0.lock().unwrap_or_else(|it| it.into_inner()) {
+            Some(it) => it.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl io::Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
 }
 
 enum MakeWriterInner {
@@ -210,7 +920,21 @@ enum MakeWriterInner {
     Stdout(io::Stdout),
     Stderr(io::Stderr),
     File(File),
+    Atomic(AtomicFile),
+    Reopen(Reopen),
+    #[cfg(feature = "os_pipe1")]
+    Pipe(os_pipe::PipeWriter),
     Rolling(RollingFileAppender),
+    DateRolling(DateRolling),
+    Retrying(Retrying<Box<MakeWriterInner>>),
+    Counting(Box<MakeWriterInner>, Arc<FileLen>),
+    Overflow(Arc<MakeWriterInner>, Overflow),
+    /// Events below `warn`, and events at `warn` and above.
+    SplitByLevel(Box<MakeWriterInner>, Box<MakeWriterInner>),
+    Collapsing(Collapsing),
+    Budgeted(Budgeted),
+    #[cfg(feature = "log0_4")]
+    LogBridge(LogBridge),
     Deferred(Arc<io::Error>),
 }
 
@@ -220,7 +944,34 @@ enum WriterInner<'a> {
     Stdout(&'a io::Stdout),
     Stderr(&'a io::Stderr),
     File(&'a File),
+    Atomic(&'a AtomicFile),
+    Reopen(&'a Reopen),
+    #[cfg(feature = "os_pipe1")]
+    Pipe(&'a os_pipe::PipeWriter),
     Rolling(RollingWriter<'a>),
+    DateRolling(&'a DateRolling),
+    /// Makes its inner writer for each attempt, with the level of the event being written.
+    Retrying(
+        &'a Retrying<Box<MakeWriterInner>>,
+        Option<tracing_core::Level>,
+    ),
+    /// Like [`Self::Retrying`].
+    Counting(
+        &'a MakeWriterInner,
+        &'a FileLen,
+        Option<tracing_core::Level>,
+    ),
+    /// Like [`Self::Counting`].
+    Overflow(
+        &'a MakeWriterInner,
+        &'a Overflow,
+        Option<tracing_core::Level>,
+    ),
+    Collapsing(CollapsingWriter<'a>),
+    /// With the level of the event being written, if any.
+    Budgeted(&'a Budgeted, Option<tracing_core::Level>),
+    #[cfg(feature = "log0_4")]
+    LogBridge(LogBridgeWriter<'a>),
     Deferred(&'a Arc<io::Error>),
 }
 
@@ -228,10 +979,32 @@ impl io::Write for WriterInner<'_> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         match self {
             WriterInner::NonBlocking(it) => it.write(buf),
-            WriterInner::Stdout(it) => it.write(buf),
-            WriterInner::Stderr(it) => it.write(buf),
+            WriterInner::Stdout(it) => ignore_broken_pipe(it.write(buf), buf.len()),
+            WriterInner::Stderr(it) => ignore_broken_pipe(it.write(buf), buf.len()),
             WriterInner::File(it) => it.write(buf),
+            WriterInner::Atomic(it) => it.write(buf),
+            WriterInner::Reopen(it) => it.write(buf),
+            #[cfg(feature = "os_pipe1")]
+            WriterInner::Pipe(it) => it.write(buf),
             WriterInner::Rolling(it) => it.write(buf),
+            WriterInner::DateRolling(it) => it.write(buf),
+            WriterInner::Retrying(it, level) => with_retries(it.max_attempts, it.backoff, || {
+                it.inner.writer_for(*level).write(buf)
+            }),
+            WriterInner::Counting(it, len, level) => {
+                let n = it.writer_for(*level).write(buf)?;
+                len.add(n);
+                Ok(n)
+            }
+            WriterInner::Overflow(it, overflow, level) => {
+                let res = it.writer_for(*level).write(buf);
+                overflow.check();
+                res
+            }
+            WriterInner::Collapsing(it) => it.write(buf),
+            WriterInner::Budgeted(it, level) => it.write(buf, *level),
+            #[cfg(feature = "log0_4")]
+            WriterInner::LogBridge(it) => it.write(buf),
             WriterInner::Null(it) => it.write(buf),
             WriterInner::Deferred(e) => Err(io::Error::new(e.kind(), Arc::clone(e))),
         }
@@ -240,28 +1013,1158 @@ impl io::Write for WriterInner<'_> {
     fn flush(&mut self) -> io::Result<()> {
         match self {
             WriterInner::NonBlocking(it) => it.flush(),
-            WriterInner::Stdout(it) => it.flush(),
-            WriterInner::Stderr(it) => it.flush(),
+            WriterInner::Stdout(it) => ignore_broken_pipe(it.flush(), ()),
+            WriterInner::Stderr(it) => ignore_broken_pipe(it.flush(), ()),
             WriterInner::File(it) => it.flush(),
+            WriterInner::Atomic(it) => it.flush(),
+            WriterInner::Reopen(it) => it.flush(),
+            #[cfg(feature = "os_pipe1")]
+            WriterInner::Pipe(it) => it.flush(),
             WriterInner::Rolling(it) => it.flush(),
+            WriterInner::DateRolling(it) => it.flush(),
+            WriterInner::Retrying(it, level) => with_retries(it.max_attempts, it.backoff, || {
+                it.inner.writer_for(*level).flush()
+            }),
+            WriterInner::Counting(it, _, level) => it.writer_for(*level).flush(),
+            WriterInner::Overflow(it, _, level) => it.writer_for(*level).flush(),
+            WriterInner::Collapsing(it) => it.flush(),
+            WriterInner::Budgeted(it, level) => it.flush(*level),
+            #[cfg(feature = "log0_4")]
+            WriterInner::LogBridge(it) => it.flush(),
             WriterInner::Null(it) => it.flush(),
             WriterInner::Deferred(e) => Err(io::Error::new(e.kind(), Arc::clone(e))),
         }
     }
 }
 
+/// A rolling file appender with a custom [`date_format`](crate::Roll::date_format)
+/// or [`current_path_file`](crate::Rolling::current_path_file),
+/// which [`RollingFileAppender`] doesn't support.
+struct DateRolling {
+    directory: PathBuf,
+    prefix: Option<String>,
+    suffix: Option<String>,
+    date_format: String,
+    rotation: crate::Rotation,
+    limit: Option<usize>,
+    current_path_file: Option<PathBuf>,
+    /// The start of the current period, in seconds since the epoch, and its file.
+    state: Mutex<(i64, Option<File>)>,
+}
+
+impl DateRolling {
+    fn open(self) -> io::Result<Self> {
+        if !crate::is_valid_strftime(&self.date_format)
+            || self.date_format.contains(std::path::is_separator)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid date format `{}`", self.date_format),
+            ));
+        }
+        fs::create_dir_all(&self.directory)?;
+        self.roll(&mut self.state.lock().unwrap_or_else(|it| it.into_inner()))?;
+        Ok(self)
+    }
+    fn filename(&self, period: i64) -> String {
+        let date = match self.rotation {
+            crate::Rotation::Never => None,
+            _ => chrono::DateTime::from_timestamp(period, 0)
+                .map(|it| it.format(&self.date_format).to_string()),
+        };
+        [
+            self.prefix.as_deref(),
+            date.as_deref(),
+            self.suffix.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(".")
+    }
+    /// Open the file for the current period if we haven't already.
+    fn roll<'a>(&self, state: &'a mut (i64, Option<File>)) -> io::Result<&'a File> {
+        let period = period(&self.rotation);
+        if state.1.is_none() || state.0 != period {
+            let path = self.directory.join(self.filename(period));
+            let file = File::options().create(true).append(true).open(&path)?;
+            if let Some(sidecar) = &self.current_path_file {
+                let mut tmp = sidecar.clone().into_os_string();
+                tmp.push(".tmp");
+                fs::write(
+                    &tmp,
+                    std::path::absolute(&path)?.as_os_str().as_encoded_bytes(),
+                )?;
+                fs::rename(&tmp, sidecar)?;
+            }
+            *state = (period, Some(file));
+            if let Some(limit) = self.limit {
+                self.prune(limit)
+            }
+        }
+        Ok(state.1.as_ref().expect("file was just opened"))
+    }
+    /// Remove all but the `limit` most recent log files.
+    fn prune(&self, limit: usize) {
+        let Ok(dir) = fs::read_dir(&self.directory) else {
+            return;
+        };
+        let mut files = dir
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let metadata = entry.metadata().ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                let date = name
+                    .strip_prefix(self.prefix.as_deref().unwrap_or_default())?
+                    .strip_suffix(self.suffix.as_deref().unwrap_or_default())?
+                    .trim_matches('.');
+                let mut parsed = chrono::format::Parsed::new();
+                chrono::format::parse(
+                    &mut parsed,
+                    date,
+                    chrono::format::StrftimeItems::new(&self.date_format),
+                )
+                .ok()?;
+                match metadata.is_file() {
+                    true => Some((metadata.modified().ok()?, entry.path())),
+                    false => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        for (_, path) in files.iter().rev().skip(limit) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl io::Write for &DateRolling {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock().unwrap_or_else(|it| it.into_inner());
+        self.roll(&mut state)?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|it| it.into_inner());
+        self.roll(&mut state)?.flush()
+    }
+}
+
+impl io::Write for DateRolling {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+}
+
+/// The number of `SIGHUP`s received, see [`crate::File::reopen_on_sighup`].
+static SIGHUPS: AtomicUsize = AtomicUsize::new(0);
+
+/// Ask every [`Reopen`] to reopen its file on the next write.
+///
+/// This is async-signal-safe.
+#[cfg(all(unix, feature = "libc0_2"))]
+fn trigger_reopen() {
+    SIGHUPS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Install a `SIGHUP` handler which calls [`trigger_reopen`],
+/// and then any handler that was installed before it.
+#[cfg(all(unix, feature = "libc0_2"))]
+fn install_sighup_handler() -> io::Result<()> {
+    /// The previous handler, if it's a function rather than e.g [`libc::SIG_DFL`].
+    static PREVIOUS: AtomicUsize = AtomicUsize::new(libc::SIG_DFL);
+    /// Whether [`PREVIOUS`] takes a [`libc::siginfo_t`].
+    static PREVIOUS_SIGINFO: std::sync::atomic::AtomicBool =
+        std::sync::atomic::AtomicBool::new(false);
+    extern "C" fn handler(
+        signal: libc::c_int,
+        info: *mut libc::siginfo_t,
+        context: *mut libc::c_void,
+    ) {
+        trigger_reopen();
+        let previous = PREVIOUS.load(Ordering::Relaxed);
+        if previous == libc::SIG_DFL || previous == libc::SIG_IGN {
+            return;
+        }
+        // SAFETY: this is how the previous handler was registered.
+        unsafe {
+            match PREVIOUS_SIGINFO.load(Ordering::Relaxed) {
+                true => std::mem::transmute::<
+                    libc::sighandler_t,
+                    extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void),
+                >(previous)(signal, info, context),
+                false => std::mem::transmute::<libc::sighandler_t, extern "C" fn(libc::c_int)>(
+                    previous,
+                )(signal),
+            }
+        }
+    }
+    static INSTALLED: std::sync::OnceLock<Result<(), io::ErrorKind>> = std::sync::OnceLock::new();
+    INSTALLED
+        .get_or_init(|| {
+            // SAFETY: the handler only touches atomics, and calls the previous handler.
+            unsafe {
+                let mut previous = std::mem::zeroed::<libc::sigaction>();
+                if libc::sigaction(libc::SIGHUP, std::ptr::null(), &mut previous) != 0 {
+                    return Err(io::Error::last_os_error().kind());
+                }
+                PREVIOUS_SIGINFO
+                    .store(previous.sa_flags & libc::SA_SIGINFO != 0, Ordering::Relaxed);
+                PREVIOUS.store(previous.sa_sigaction, Ordering::Relaxed);
+                let mut action = std::mem::zeroed::<libc::sigaction>();
+                action.sa_sigaction = handler
+                    as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void)
+                    as libc::sighandler_t;
+                action.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO;
+                libc::sigemptyset(&mut action.sa_mask);
+                match libc::sigaction(libc::SIGHUP, &action, std::ptr::null_mut()) {
+                    0 => Ok(()),
+                    _ => Err(io::Error::last_os_error().kind()),
+                }
+            }
+        })
+        .map_err(|kind| io::Error::new(kind, "couldn't install a SIGHUP handler"))
+}
+
+#[cfg(not(all(unix, feature = "libc0_2")))]
+fn install_sighup_handler() -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reopening log files on SIGHUP is only supported on unix, with the `libc` feature",
+    ))
+}
+
+/// A [`File`] which is reopened after a `SIGHUP`, see [`crate::File::reopen_on_sighup`].
+struct Reopen {
+    path: PathBuf,
+    /// The value of [`SIGHUPS`] when the file was opened, and the file.
+    state: Mutex<(usize, File)>,
+}
+
+impl Reopen {
+    fn new(path: PathBuf, file: File) -> io::Result<Self> {
+        install_sighup_handler()?;
+        Ok(Self {
+            path,
+            state: Mutex::new((SIGHUPS.load(Ordering::Relaxed), file)),
+        })
+    }
+    /// Reopen the file if there's been a `SIGHUP` since we last did.
+    fn reopen<'a>(&self, state: &'a mut (usize, File)) -> &'a File {
+        let sighups = SIGHUPS.load(Ordering::Relaxed);
+        if state.0 != sighups {
+            state.0 = sighups;
+            if let Ok(file) = File::options().create(true).append(true).open(&self.path) {
+                state.1 = file
+            }
+        }
+        &state.1
+    }
+}
+
+impl io::Write for &Reopen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock().unwrap_or_else(|it| it.into_inner());
+        self.reopen(&mut state).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|it| it.into_inner());
+        self.reopen(&mut state).flush()
+    }
+}
+
+impl io::Write for Reopen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+}
+
+/// If the reader of a pipe has gone away (e.g `my-app | head`),
+/// pretend the write succeeded, as is conventional for CLI tools.
+fn ignore_broken_pipe<T>(res: io::Result<T>, or: T) -> io::Result<T> {
+    match res {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(or),
+        res => res,
+    }
+}
+
+impl MakeWriterInner {
+    /// See [`MakeWriter::on_overflow`].
+    fn error_counters(&self, out: &mut Vec<ErrorCounter>) {
+        match self {
+            MakeWriterInner::NonBlocking(it) => out.push(it.error_counter()),
+            MakeWriterInner::Retrying(it) => it.inner.error_counters(out),
+            MakeWriterInner::Counting(it, _) => it.error_counters(out),
+            MakeWriterInner::Overflow(it, _) => it.error_counters(out),
+            MakeWriterInner::SplitByLevel(below, above) => {
+                below.error_counters(out);
+                above.error_counters(out)
+            }
+            MakeWriterInner::Collapsing(it) => it.inner.error_counters(out),
+            MakeWriterInner::Budgeted(it) => it.inner.error_counters(out),
+            MakeWriterInner::Null(_)
+            | MakeWriterInner::Stdout(_)
+            | MakeWriterInner::Stderr(_)
+            | MakeWriterInner::File(_)
+            | MakeWriterInner::Atomic(_)
+            | MakeWriterInner::Reopen(_)
+            | MakeWriterInner::Rolling(_)
+            | MakeWriterInner::DateRolling(_)
+            | MakeWriterInner::Deferred(_) => {}
+            #[cfg(feature = "os_pipe1")]
+            MakeWriterInner::Pipe(_) => {}
+            #[cfg(feature = "log0_4")]
+            MakeWriterInner::LogBridge(_) => {}
+        }
+    }
+    /// A writer for an event at `level`, or for e.g the shutdown summary if [`None`].
+    fn writer_for(&self, level: Option<tracing_core::Level>) -> WriterInner<'_> {
+        use tracing_subscriber::fmt::MakeWriter as _;
+        match self {
+            MakeWriterInner::NonBlocking(it) => WriterInner::NonBlocking(it.make_writer()),
+            MakeWriterInner::Stdout(it) => WriterInner::Stdout(it),
+            MakeWriterInner::Stderr(it) => WriterInner::Stderr(it),
+            MakeWriterInner::File(it) => WriterInner::File(it.make_writer()),
+            MakeWriterInner::Atomic(it) => WriterInner::Atomic(it),
+            MakeWriterInner::Reopen(it) => WriterInner::Reopen(it),
+            #[cfg(feature = "os_pipe1")]
+            MakeWriterInner::Pipe(it) => WriterInner::Pipe(it),
+            MakeWriterInner::Rolling(it) => WriterInner::Rolling(it.make_writer()),
+            MakeWriterInner::DateRolling(it) => WriterInner::DateRolling(it),
+            MakeWriterInner::Retrying(it) => WriterInner::Retrying(it, level),
+            MakeWriterInner::Counting(it, len) => WriterInner::Counting(it, len, level),
+            MakeWriterInner::Overflow(it, overflow) => WriterInner::Overflow(it, overflow, level),
+            MakeWriterInner::Budgeted(it) => WriterInner::Budgeted(it, level),
+            MakeWriterInner::Collapsing(it) => WriterInner::Collapsing(CollapsingWriter {
+                collapsing: it,
+                level,
+                partial: vec![],
+            }),
+            #[cfg(feature = "log0_4")]
+            MakeWriterInner::LogBridge(it) => WriterInner::LogBridge(LogBridgeWriter {
+                bridge: it,
+                partial: vec![],
+            }),
+            // Writes without an event, e.g the shutdown summary, aren't warnings.
+            MakeWriterInner::SplitByLevel(below, above) => match level {
+                Some(level) if level <= tracing_core::Level::WARN => above.writer_for(Some(level)),
+                _ => below.writer_for(level),
+            },
+            MakeWriterInner::Null(it) => WriterInner::Null(it),
+            MakeWriterInner::Deferred(it) => WriterInner::Deferred(it),
+        }
+    }
+}
+
 impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for MakeWriterInner {
     type Writer = WriterInner<'a>;
 
     fn make_writer(&'a self) -> Self::Writer {
-        match self {
-            MakeWriterInner::NonBlocking(it) => Self::Writer::NonBlocking(it.make_writer()),
-            MakeWriterInner::Stdout(it) => Self::Writer::Stdout(it),
-            MakeWriterInner::Stderr(it) => Self::Writer::Stderr(it),
-            MakeWriterInner::File(it) => Self::Writer::File(it.make_writer()),
-            MakeWriterInner::Rolling(it) => Self::Writer::Rolling(it.make_writer()),
-            MakeWriterInner::Null(it) => Self::Writer::Null(it),
-            MakeWriterInner::Deferred(it) => Self::Writer::Deferred(it),
+        self.writer_for(None)
+    }
+
+    fn make_writer_for(&'a self, meta: &tracing_core::Metadata<'_>) -> Self::Writer {
+        self.writer_for(Some(*meta.level()))
+    }
+}
+
+/// See [`crate::Writer::LogBridge`].
+#[cfg(feature = "log0_4")]
+struct LogBridge {
+    level: log::Level,
+    target: String,
+}
+
+#[cfg(feature = "log0_4")]
+impl LogBridge {
+    fn log(&self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        log::logger().log(
+            &log::Record::builder()
+                .level(self.level)
+                .target(&self.target)
+                .args(format_args!("{}", line.trim_end_matches(['\r', '\n'])))
+                .build(),
+        )
+    }
+}
+
+/// Buffers until a line is complete, see [`LogBridge`].
+#[cfg(feature = "log0_4")]
+struct LogBridgeWriter<'a> {
+    bridge: &'a LogBridge,
+    partial: Vec<u8>,
+}
+
+#[cfg(feature = "log0_4")]
+impl io::Write for LogBridgeWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.partial.extend_from_slice(buf);
+        while let Some(ix) = self.partial.iter().position(|it| *it == b'\n') {
+            let line = self.partial.drain(..=ix).collect::<Vec<_>>();
+            self.bridge.log(&line)
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.partial.is_empty() {
+            self.bridge.log(&std::mem::take(&mut self.partial))
+        }
+        log::logger().flush();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "log0_4")]
+impl Drop for LogBridgeWriter<'_> {
+    fn drop(&mut self) {
+        if !self.partial.is_empty() {
+            self.bridge.log(&self.partial)
         }
     }
 }
+
+/// See [`Format::collapse_repeats`](crate::Format::collapse_repeats).
+struct Collapsing {
+    inner: Arc<MakeWriterInner>,
+    /// The last line written, its level, and how many times it has been repeated since.
+    state: Mutex<(Option<Vec<u8>>, Option<tracing_core::Level>, u64)>,
+}
+
+impl Collapsing {
+    fn write_line(&self, line: &[u8], level: Option<tracing_core::Level>) -> io::Result<()> {
+        use io::Write as _;
+        let mut state = self.state.lock().unwrap_or_else(|it| it.into_inner());
+        let (last, last_level, repeated) = &mut *state;
+        if last.as_deref() == Some(line) {
+            *repeated += 1;
+            return Ok(());
+        }
+        // the summary goes with the line it repeats
+        Self::summarize(&mut self.inner.writer_for(*last_level), repeated)?;
+        self.inner.writer_for(level).write_all(line)?;
+        *last = Some(line.to_vec());
+        *last_level = level;
+        Ok(())
+    }
+    fn flush(&self) -> io::Result<()> {
+        use io::Write as _;
+        let mut state = self.state.lock().unwrap_or_else(|it| it.into_inner());
+        let (last, last_level, repeated) = &mut *state;
+        let mut writer = self.inner.writer_for(*last_level);
+        if *repeated != 0 {
+            Self::summarize(&mut writer, repeated)?;
+            // the summary broke the run
+            *last = None;
+        }
+        writer.flush()
+    }
+    fn summarize(writer: &mut WriterInner, repeated: &mut u64) -> io::Result<()> {
+        use io::Write as _;
+        match std::mem::take(repeated) {
+            0 => Ok(()),
+            1 => writeln!(writer, "last message repeated 1 time"),
+            n => writeln!(writer, "last message repeated {n} times"),
+        }
+    }
+}
+
+/// Drops writes once `bytes` have been written in the current `window`,
+/// see [`crate::Subscriber::total_byte_budget`].
+struct Budgeted {
+    inner: Arc<MakeWriterInner>,
+    bytes: u64,
+    window: Duration,
+    /// The start of the current window, the bytes written in it,
+    /// and how many writes have been dropped since the last summary.
+    state: Mutex<(Instant, u64, u64)>,
+}
+
+impl Budgeted {
+    fn write(&self, buf: &[u8], level: Option<tracing_core::Level>) -> io::Result<usize> {
+        use io::Write as _;
+        let mut state = self.state.lock().unwrap_or_else(|it| it.into_inner());
+        let (start, written, suppressed) = &mut *state;
+        let mut writer = self.inner.writer_for(level);
+        // writes without an event, e.g the shutdown summary, aren't budgeted
+        if level.is_none() {
+            Self::summarize(&mut writer, suppressed)?;
+            writer.write_all(buf)?;
+            return Ok(buf.len());
+        }
+        if start.elapsed() >= self.window {
+            *start = Instant::now();
+            *written = 0;
+            Self::summarize(&mut writer, suppressed)?;
+        }
+        match *written < self.bytes {
+            true => {
+                writer.write_all(buf)?;
+                *written += buf.len() as u64;
+            }
+            false => *suppressed += 1,
+        }
+        Ok(buf.len())
+    }
+    fn flush(&self, level: Option<tracing_core::Level>) -> io::Result<()> {
+        use io::Write as _;
+        let mut state = self.state.lock().unwrap_or_else(|it| it.into_inner());
+        let mut writer = self.inner.writer_for(level);
+        Self::summarize(&mut writer, &mut state.2)?;
+        writer.flush()
+    }
+    fn summarize(writer: &mut WriterInner, suppressed: &mut u64) -> io::Result<()> {
+        use io::Write as _;
+        match std::mem::take(suppressed) {
+            0 => Ok(()),
+            1 => writeln!(
+                writer,
+                "1 event suppressed, the log byte budget was exceeded"
+            ),
+            n => writeln!(
+                writer,
+                "{n} events suppressed, the log byte budget was exceeded"
+            ),
+        }
+    }
+}
+
+/// Buffers until a line is complete, see [`Collapsing`].
+struct CollapsingWriter<'a> {
+    collapsing: &'a Collapsing,
+    level: Option<tracing_core::Level>,
+    partial: Vec<u8>,
+}
+
+impl io::Write for CollapsingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.partial.extend_from_slice(buf);
+        while let Some(ix) = self.partial.iter().position(|it| *it == b'\n') {
+            let line = self.partial.drain(..=ix).collect::<Vec<_>>();
+            self.collapsing.write_line(&line, self.level)?
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.partial.is_empty() {
+            self.collapsing
+                .write_line(&std::mem::take(&mut self.partial), self.level)?
+        }
+        self.collapsing.flush()
+    }
+}
+
+impl Drop for CollapsingWriter<'_> {
+    fn drop(&mut self) {
+        if !self.partial.is_empty() {
+            let _ = self.collapsing.write_line(&self.partial, self.level);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+#[test]
+fn broken_pipe() {
+    use io::Write as _;
+    let mut child = std::process::Command::new("true")
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    child.wait().unwrap();
+    for _ in 0..2 {
+        let e = stdin.write_all(b"hello\n").unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+        assert_eq!(ignore_broken_pipe(Err(e), 6).unwrap(), 6);
+        ignore_broken_pipe(stdin.flush(), ()).unwrap();
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn date_format() {
+    use io::Write as _;
+    use tracing_subscriber::fmt::MakeWriter as _;
+    let dir = tempfile::tempdir().unwrap();
+    let (writer, _guard) = MakeWriter::try_new(crate::Writer::Rolling(crate::Rolling {
+        directory: dir.path().to_path_buf().into(),
+        roll: Some(crate::Roll {
+            prefix: Some(String::from("app")),
+            suffix: Some(String::from("log")),
+            rotation: Some(crate::Rotation::Daily),
+            date_format: Some(String::from("%Y_%m_%d")),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }))
+    .unwrap();
+    writer.make_writer().write_all(b"hello\n").unwrap();
+    let expected = format!("app.{}.log", chrono::Utc::now().format("%Y_%m_%d"));
+    assert_eq!(
+        fs::read_to_string(dir.path().join(expected)).unwrap(),
+        "hello\n"
+    );
+}
+
+#[cfg(all(test, unix, feature = "libc0_2"))]
+#[test]
+fn reopen_on_sighup() {
+    use io::Write as _;
+    use std::sync::atomic::AtomicBool;
+    use tracing_subscriber::fmt::MakeWriter as _;
+    // e.g an application's own reload-on-SIGHUP
+    static APPLICATION: AtomicBool = AtomicBool::new(false);
+    extern "C" fn application(_: libc::c_int) {
+        APPLICATION.store(true, Ordering::Relaxed)
+    }
+    unsafe {
+        libc::signal(
+            libc::SIGHUP,
+            application as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        )
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (writer, _guard) = MakeWriter::try_new(crate::Writer::File(crate::File {
+        path: path.clone().into(),
+        reopen_on_sighup: Some(true),
+        ..Default::default()
+    }))
+    .unwrap();
+    writer.make_writer().write_all(b"before\n").unwrap();
+    fs::rename(&path, dir.path().join("log.1")).unwrap();
+    writer.make_writer().write_all(b"renamed\n").unwrap();
+    assert_eq!(unsafe { libc::raise(libc::SIGHUP) }, 0);
+    writer.make_writer().write_all(b"after\n").unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("log.1")).unwrap(),
+        "before\nrenamed\n"
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), "after\n");
+    assert!(APPLICATION.load(Ordering::Relaxed));
+}
+
+#[cfg(all(test, not(all(unix, feature = "libc0_2"))))]
+#[test]
+fn reopen_on_sighup_unsupported() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = crate::File {
+        path: dir.path().join("log").into(),
+        reopen_on_sighup: Some(true),
+        ..Default::default()
+    };
+    assert!(MakeWriter::try_new(crate::Writer::File(file.clone())).is_err());
+    // deferred rather than panicking
+    let _ = MakeWriter::new(crate::Writer::File(file));
+}
+
+#[cfg(test)]
+#[test]
+fn atomic() {
+    use io::Write as _;
+    use tracing_subscriber::fmt::MakeWriter as _;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (writer, guard) = MakeWriter::try_new(crate::Writer::File(crate::File {
+        path: path.clone().into(),
+        atomic: Some(true),
+        non_blocking: Some(crate::NonBlocking::default()),
+        ..Default::default()
+    }))
+    .unwrap();
+    writer.make_writer().write_all(b"hello\n").unwrap();
+    assert!(!path.exists());
+    drop(guard);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "hello\n");
+    assert!(!dir.path().join("log.tmp").exists());
+
+    // The file is closed before it's renamed, even though the writer outlives the guard.
+    let (writer, guard) = MakeWriter::try_new(crate::Writer::File(crate::File {
+        path: path.clone().into(),
+        atomic: Some(true),
+        ..Default::default()
+    }))
+    .unwrap();
+    writer.make_writer().write_all(b"world\n").unwrap();
+    drop(guard);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "world\n");
+    assert!(writer.make_writer().write_all(b"again\n").is_err());
+
+    assert!(MakeWriter::try_new(crate::Writer::File(crate::File {
+        path: path.into(),
+        atomic: Some(true),
+        mode: crate::FileOpenMode::Append,
+        ..Default::default()
+    }))
+    .is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn current_path_file() {
+    use io::Write as _;
+    let dir = tempfile::tempdir().unwrap();
+    let sidecar = dir.path().join("current");
+    let (writer, _guard) = MakeWriter::try_new(crate::Writer::Rolling(crate::Rolling {
+        directory: dir.path().join("logs").into(),
+        roll: Some(crate::Roll {
+            prefix: Some(String::from("app")),
+            rotation: Some(crate::Rotation::Daily),
+            ..Default::default()
+        }),
+        current_path_file: Some(sidecar.clone()),
+        ..Default::default()
+    }))
+    .unwrap();
+    let current = || PathBuf::from(fs::read_to_string(&sidecar).unwrap());
+    assert!(current().is_absolute() && current().is_file());
+
+    let MakeWriterInner::Counting(inner, _) = &*writer.0 else {
+        panic!()
+    };
+    let MakeWriterInner::DateRolling(rolling) = &**inner else {
+        panic!()
+    };
+    fs::remove_file(&sidecar).unwrap();
+    *rolling.state.lock().unwrap() = (0, None);
+    (&*rolling).write_all(b"hello\n").unwrap();
+    assert_eq!(fs::read_to_string(current()).unwrap(), "hello\n");
+}
+
+#[cfg(test)]
+#[test]
+fn retry() {
+    use io::Write as _;
+    struct Flaky {
+        failures: usize,
+        buf: Vec<u8>,
+    }
+    impl io::Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            match self.failures.checked_sub(1) {
+                Some(it) => {
+                    self.failures = it;
+                    Err(io::ErrorKind::WouldBlock.into())
+                }
+                None => self.buf.write(buf),
+            }
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    let retrying = |max_attempts| {
+        Retrying::new(
+            Flaky {
+                failures: 2,
+                buf: vec![],
+            },
+            Some(crate::Retry {
+                max_attempts: Some(max_attempts),
+                backoff: Some(Duration::ZERO),
+            }),
+        )
+    };
+    let mut writer = retrying(3);
+    writer.write_all(b"hello\n").unwrap();
+    assert_eq!(writer.inner.buf, b"hello\n");
+    assert!(retrying(2).write_all(b"hello\n").is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn instrumented_null() {
+    let (writer, guard, stats) = MakeWriter::instrumented_null(Duration::from_millis(20));
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .without_time()
+        .with_writer(writer)
+        .finish();
+    tracing::subscriber::with_default(subscriber, || {
+        for _ in 0..10 {
+            tracing::info!("hello")
+        }
+    });
+    drop(guard);
+    assert_eq!(
+        stats.bytes(),
+        10 * " INFO tracing_configuration::writer: hello\n".len() as u64
+    );
+    assert!(stats.writes() >= 1);
+    assert!(stats.flushes() >= 1);
+}
+
+#[cfg(test)]
+#[test]
+fn barrier() {
+    use io::Write as _;
+    use tracing_subscriber::fmt::MakeWriter as _;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (writer, guard) = MakeWriter::try_new(crate::Writer::File(crate::File {
+        path: path.clone().into(),
+        non_blocking: Some(crate::NonBlocking::default()),
+        ..Default::default()
+    }))
+    .unwrap();
+    writer.make_writer().write_all(b"first\n").unwrap();
+    guard.barrier().wait();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "first\n");
+    writer.make_writer().write_all(b"second\n").unwrap();
+    guard.barrier().wait();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+    let barrier = guard.barrier();
+    drop(guard);
+    barrier.wait();
+}
+
+#[cfg(test)]
+#[test]
+fn collapse_repeats() {
+    use io::Write as _;
+    use tracing_subscriber::fmt::MakeWriter as _;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (writer, _guard) = MakeWriter::try_new(crate::Writer::File(crate::File {
+        path: path.clone().into(),
+        ..Default::default()
+    }))
+    .unwrap();
+    let writer = writer.collapse_repeats();
+    for line in ["same\n", "same\n", "same\n", "different\n", "different\n"] {
+        writer.make_writer().write_all(line.as_bytes()).unwrap()
+    }
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "same\nlast message repeated 2 times\ndifferent\n"
+    );
+    writer.make_writer().flush().unwrap();
+    writer.make_writer().write_all(b"different\n").unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "same\nlast message repeated 2 times\ndifferent\nlast message repeated 1 time\ndifferent\n"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn thread_name() {
+    use io::Write as _;
+    struct ThreadName(Arc<Mutex<Option<String>>>);
+    impl io::Write for ThreadName {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            *self.0.lock().unwrap() = std::thread::current().name().map(String::from);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    let name = Arc::default();
+    let (mut writer, worker) = crate::NonBlocking {
+        thread_name: Some(String::from("my-app-logs")),
+        ..Default::default()
+    }
+    .build(ThreadName(Arc::clone(&name)));
+    writer.write_all(b"hello\n").unwrap();
+    drop(worker);
+    assert_eq!(name.lock().unwrap().as_deref(), Some("my-app-logs"));
+}
+
+#[cfg(all(test, feature = "os_pipe1"))]
+#[test]
+fn os_pipe() {
+    use io::BufRead as _;
+    let (writer, reader) = MakeWriter::os_pipe().unwrap();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(false)
+        .without_time()
+        .finish();
+    tracing::subscriber::with_default(subscriber, || tracing::info!("hello"));
+    let mut line = String::new();
+    io::BufReader::new(reader).read_line(&mut line).unwrap();
+    assert!(line.ends_with("hello\n"), "{line}");
+}
+
+#[cfg(test)]
+#[test]
+fn byte_budget() {
+    use io::Write as _;
+    use tracing_subscriber::fmt::MakeWriter as _;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (writer, _guard) = MakeWriter::try_new(crate::Writer::File(crate::File {
+        path: path.clone().into(),
+        ..Default::default()
+    }))
+    .unwrap();
+    let writer = writer.byte_budget(8, Duration::from_millis(200));
+    let event = || writer.0.writer_for(Some(tracing_core::Level::INFO));
+    for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+        event().write_all(line.as_bytes()).unwrap()
+    }
+    assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+    std::thread::sleep(Duration::from_millis(200));
+    event().write_all(b"fifth\n").unwrap();
+    event().write_all(b"sixth\n").unwrap();
+    writer.make_writer().flush().unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "first\nsecond\n2 events suppressed, the log byte budget was exceeded\nfifth\nsixth\n"
+    );
+    event().write_all(b"seventh\n").unwrap();
+    writer.make_writer().flush().unwrap();
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .ends_with("sixth\n1 event suppressed, the log byte budget was exceeded\n"));
+    // writes without an event aren't budgeted
+    event().write_all(b"eighth\n").unwrap();
+    writer.make_writer().write_all(b"summary\n").unwrap();
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .ends_with("exceeded\n1 event suppressed, the log byte budget was exceeded\nsummary\n"));
+}
+
+#[cfg(all(test, feature = "os_pipe1"))]
+#[test]
+fn split_by_level() {
+    use io::Read as _;
+    let (mut stdout_reader, stdout) = os_pipe::pipe().unwrap();
+    let (mut stderr_reader, stderr) = os_pipe::pipe().unwrap();
+    let writer = MakeWriter(Arc::new(MakeWriterInner::SplitByLevel(
+        Box::new(MakeWriterInner::Pipe(stdout)),
+        Box::new(MakeWriterInner::Pipe(stderr)),
+    )));
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(false)
+        .without_time()
+        .with_max_level(tracing_core::Level::TRACE)
+        .finish();
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::trace!("trace");
+        tracing::info!("info");
+        tracing::warn!("warn");
+        tracing::error!("error");
+    });
+    let (mut stdout, mut stderr) = (String::new(), String::new());
+    stdout_reader.read_to_string(&mut stdout).unwrap();
+    stderr_reader.read_to_string(&mut stderr).unwrap();
+    assert_eq!(
+        stdout,
+        "TRACE writer: trace\n INFO writer: info\n".replace("writer", module_path!())
+    );
+    assert_eq!(
+        stderr,
+        " WARN writer: warn\nERROR writer: error\n".replace("writer", module_path!())
+    );
+}
+
+/// Runs [`std_split_collapse_repeats_child`] in a subprocess to see what it writes to stdout and stderr.
+#[cfg(test)]
+#[test]
+fn std_split_collapse_repeats() {
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "writer::std_split_collapse_repeats_child",
+            "--test-threads=1",
+        ])
+        .env("TRACING_CONFIGURATION_STD_SPLIT_CHILD", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    // the harness shares stdout, and doesn't end its line before the first event
+    let events = |s: &str| {
+        s.lines()
+            .filter(|it| it.contains("EVENT") || it.starts_with("last message"))
+            .map(|it| it.rsplit("... ").next().unwrap().trim().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        events(&stdout),
+        ["INFO EVENT info", "INFO EVENT info again"]
+    );
+    assert_eq!(
+        events(&stderr),
+        ["ERROR EVENT error", "last message repeated 1 time"]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn std_split_collapse_repeats_child() {
+    if std::env::var_os("TRACING_CONFIGURATION_STD_SPLIT_CHILD").is_none() {
+        return;
+    }
+    let (builder, _guard) = crate::Subscriber {
+        format: Some(crate::Format {
+            collapse_repeats: Some(true),
+            target: Some(false),
+            ..crate::plain()
+        }),
+        writer: Some(crate::Writer::StdSplit),
+        filter: Some(crate::Filter {
+            directives: vec!["trace".parse().unwrap()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .builder();
+    tracing::subscriber::with_default(builder.finish(), || {
+        tracing::info!("EVENT info");
+        tracing::error!("EVENT error");
+        tracing::error!("EVENT error");
+        tracing::info!("EVENT info again");
+    });
+}
+
+#[cfg(test)]
+#[test]
+fn on_overflow() {
+    use io::Write as _;
+    use tracing_subscriber::fmt::MakeWriter as _;
+    /// Blocks the worker until the test releases the lock.
+    struct Stuck(Arc<Mutex<usize>>);
+    impl io::Write for Stuck {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            *self.0.lock().unwrap() += 1;
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    let written = Arc::new(Mutex::new(0));
+    let dropped = Arc::new(AtomicU64::new(0));
+    let lock = written.lock().unwrap();
+    let (sender, worker) = crate::NonBlocking {
+        buffer_length: Some(1),
+        behaviour: Some(crate::BackpressureBehaviour::Drop),
+        ..Default::default()
+    }
+    .build(Stuck(written.clone()));
+    let writer = MakeWriter(Arc::new(MakeWriterInner::NonBlocking(sender)))
+        .on_overflow(crate::OverflowAction::Count(dropped.clone()));
+    for _ in 0..10 {
+        writer.make_writer().write_all(b"hello\n").unwrap();
+    }
+    drop(lock);
+    drop(worker);
+    let dropped = dropped.load(Ordering::Relaxed);
+    assert!(dropped > 0);
+    assert_eq!(dropped as usize + *written.lock().unwrap(), 10);
+}
+
+#[cfg(all(test, not(feature = "log0_4")))]
+#[test]
+fn log_bridge_disabled() {
+    assert!(MakeWriter::try_new(crate::Writer::LogBridge(crate::LogBridge::default())).is_err());
+}
+
+#[cfg(all(test, feature = "log0_4"))]
+#[test]
+fn log_bridge() {
+    use io::Write as _;
+    use tracing_subscriber::fmt::MakeWriter as _;
+    struct Capture(Mutex<Vec<(log::Level, String, String)>>);
+    impl log::Log for Capture {
+        fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+            true
+        }
+        fn log(&self, record: &log::Record<'_>) {
+            self.0.lock().unwrap().push((
+                record.level(),
+                record.target().into(),
+                record.args().to_string(),
+            ))
+        }
+        fn flush(&self) {}
+    }
+    static CAPTURE: Capture = Capture(Mutex::new(vec![]));
+    log::set_logger(&CAPTURE).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+    let (writer, _guard) = MakeWriter::try_new(crate::Writer::LogBridge(crate::LogBridge {
+        level: Some(crate::Level::Warn),
+        target: None,
+    }))
+    .unwrap();
+    writer.make_writer().write_all(b"hello\nwor").unwrap();
+    let mut it = writer.make_writer();
+    it.write_all(b"ld\n").unwrap();
+    it.write_all(b"partial").unwrap();
+    drop(it);
+    assert_eq!(
+        *CAPTURE.0.lock().unwrap(),
+        [
+            (
+                log::Level::Warn,
+                String::from("tracing"),
+                String::from("hello")
+            ),
+            (
+                log::Level::Warn,
+                String::from("tracing"),
+                String::from("wor")
+            ),
+            (
+                log::Level::Warn,
+                String::from("tracing"),
+                String::from("ld")
+            ),
+            (
+                log::Level::Warn,
+                String::from("tracing"),
+                String::from("partial")
+            ),
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn current_file_len() {
+    use io::Write as _;
+    use tracing_subscriber::fmt::MakeWriter as _;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    fs::write(&path, "existing\n").unwrap();
+    let (writer, guard) = MakeWriter::try_new(crate::Writer::File(crate::File {
+        path: path.clone().into(),
+        mode: crate::FileOpenMode::Append,
+        ..Default::default()
+    }))
+    .unwrap();
+    assert_eq!(guard.current_file_len(), Some(9));
+    writer.make_writer().write_all(b"hello\n").unwrap();
+    assert_eq!(guard.current_file_len(), Some(15));
+    assert_eq!(fs::metadata(&path).unwrap().len(), 15);
+
+    let (writer, guard) = MakeWriter::try_new(crate::Writer::Rolling(crate::Rolling {
+        directory: dir.path().join("rolling").into(),
+        ..Default::default()
+    }))
+    .unwrap();
+    writer.make_writer().write_all(b"hello\n").unwrap();
+    assert_eq!(guard.current_file_len(), Some(6));
+
+    assert_eq!(
+        MakeWriter::new(crate::Writer::Stdout).1.current_file_len(),
+        None
+    );
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "log0_4")]
+
+use tracing_configuration::{File, Filter, Format, Subscriber, Writer};
+
+// Installs global loggers, so lives in its own test binary.
+#[test]
+fn log_bridge() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (guard, _handle) = Subscriber {
+        format: Some(Format {
+            ansi: Some(false),
+            target: Some(false),
+            ..Default::default()
+        }),
+        writer: Some(Writer::File(File {
+            path: path.clone().into(),
+            ..Default::default()
+        })),
+        filter: Some(Filter {
+            directives: vec!["info".parse().unwrap()],
+            ..Default::default()
+        }),
+        log_bridge: None,
+        total_byte_budget: None,
+    }
+    .with_log_bridge()
+    .try_init_with_reload()
+    .unwrap();
+    assert_eq!(log::max_level(), log::LevelFilter::Info);
+    log::info!("hello");
+    log::debug!("filtered");
+    drop(guard);
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains("INFO hello"), "{written}");
+    assert!(!written.contains("filtered"), "{written}");
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "log0_4")]
+
+use tracing_configuration::{File, Filter, Format, Subscriber, Writer};
+
+// Installs global loggers, so lives in its own test binary.
+#[test]
+fn log_bridge_existing_logger() {
+    // as if by `tracing-subscriber`'s `tracing-log` feature
+    tracing_log::LogTracer::init().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (guard, _handle) = Subscriber {
+        format: Some(Format {
+            ansi: Some(false),
+            target: Some(false),
+            ..Default::default()
+        }),
+        writer: Some(Writer::File(File {
+            path: path.clone().into(),
+            ..Default::default()
+        })),
+        filter: Some(Filter {
+            directives: vec!["info".parse().unwrap()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .with_log_bridge()
+    .try_init_with_reload()
+    .unwrap();
+    log::info!("hello");
+    drop(guard);
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains("INFO hello"), "{written}");
+}
@@ -0,0 +1,38 @@
+use tracing_configuration::{File, Filter, Format, Subscriber, Writer};
+
+// Installs a global subscriber, so lives in its own test binary.
+#[test]
+fn try_init_with_reload() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let (guard, handle) = Subscriber {
+        format: Some(Format {
+            ansi: Some(false),
+            ..Default::default()
+        }),
+        writer: Some(Writer::File(File {
+            path: path.clone().into(),
+            ..Default::default()
+        })),
+        filter: Some(Filter {
+            directives: vec!["try_init_with_reload=info".parse().unwrap()],
+            ..Default::default()
+        }),
+        log_bridge: None,
+        total_byte_budget: None,
+    }
+    .try_init_with_reload()
+    .unwrap();
+    tracing::debug!(target: "try_init_with_reload", "before");
+    handle
+        .reload(Filter {
+            directives: vec!["try_init_with_reload=debug".parse().unwrap()],
+            ..Default::default()
+        })
+        .unwrap();
+    tracing::debug!(target: "try_init_with_reload", "after");
+    drop(guard);
+    let s = std::fs::read_to_string(path).unwrap();
+    assert!(!s.contains("before"), "{s}");
+    assert!(s.contains("after"), "{s}");
+}